@@ -0,0 +1,254 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::{Error, Field, Record, SchemaOne, Signature};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// Represents an operation over all records matching a filter.
+pub enum Aggregate {
+    /// Number of records.
+    Count,
+    /// Sum of a numeric column.
+    Sum(usize),
+    /// Smallest value of a column.
+    Min(usize),
+    /// Largest value of a column.
+    Max(usize),
+    /// Mean of a numeric column.
+    Average(usize),
+}
+
+impl Aggregate {
+    /// Returns the column operated on.
+    pub fn column(&self) -> Option<usize> {
+        match self {
+            Self::Count => None,
+            Self::Sum(column) | Self::Min(column) | Self::Max(column) | Self::Average(column) => {
+                Some(*column)
+            }
+        }
+    }
+}
+
+impl Display for Aggregate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Count => f.write_str("count"),
+            Self::Sum(column) => f.write_fmt(format_args!("sum {column}")),
+            Self::Min(column) => f.write_fmt(format_args!("min {column}")),
+            Self::Max(column) => f.write_fmt(format_args!("max {column}")),
+            Self::Average(column) => f.write_fmt(format_args!("average {column}")),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// Represents aggregates of records with the same values in the grouped columns.
+pub struct Group {
+    key: Vec<Field>,
+    values: Vec<Option<Field>>,
+}
+
+impl Group {
+    /// Returns values of the grouped columns.
+    pub fn key(&self) -> &[Field] {
+        &self.key
+    }
+
+    /// Returns the result of each aggregate, in the order requested.
+    pub fn values(&self) -> &[Option<Field>] {
+        &self.values
+    }
+}
+
+/// Computes aggregates one record at a time.
+#[derive(Debug, Clone)]
+pub(crate) struct Aggregator {
+    label: String,
+    accs: Vec<(Aggregate, Acc)>,
+}
+
+#[derive(Debug, Clone)]
+enum Acc {
+    Count(u64),
+    Int(i128, bool),
+    Float(f64, u64),
+    Min(Option<Field>),
+    Max(Option<Field>),
+}
+
+impl Aggregator {
+    /// Constructs new self, fails if an aggregate does not fit the definition.
+    pub fn new(schema: &SchemaOne, aggregates: &[Aggregate]) -> Result<Self, Error> {
+        let mut accs = Vec::with_capacity(aggregates.len());
+
+        for aggregate in aggregates {
+            let sig = match aggregate
+                .column()
+                .map(|column| (column, schema.sig().get(column)))
+            {
+                Some((_, Some(sig))) => Some(sig.clone()),
+                Some((column, None)) => {
+                    return Err(Error::UnknownColumn {
+                        label: schema.label().to_string(),
+                        column: column.to_string(),
+                    })
+                }
+                None => None,
+            };
+
+            // pattern values have no sum or order with other values
+            let pattern = aggregate
+                .column()
+                .is_some_and(|column| schema.columns()[column].pattern());
+
+            let acc = match (aggregate, sig) {
+                (Aggregate::Count, _) => Acc::Count(0),
+                (_, Some(got)) if pattern => {
+                    return Err(Error::AggregateMismatch {
+                        label: schema.label().to_string(),
+                        aggregate: *aggregate,
+                        got,
+                    })
+                }
+                (Aggregate::Min(_), _) => Acc::Min(None),
+                (Aggregate::Max(_), _) => Acc::Max(None),
+                (Aggregate::Sum(_), Some(sig)) if sig.integer() => Acc::Int(
+                    0,
+                    matches!(
+                        sig,
+                        Signature::U8 | Signature::U16 | Signature::U32 | Signature::U64
+                    ),
+                ),
+                (Aggregate::Sum(_) | Aggregate::Average(_), Some(sig)) if sig.numeric() => {
+                    Acc::Float(0.0, 0)
+                }
+                (_, Some(got)) => {
+                    return Err(Error::AggregateMismatch {
+                        label: schema.label().to_string(),
+                        aggregate: *aggregate,
+                        got,
+                    })
+                }
+                (_, None) => unreachable!("aggregates other than count have a column"),
+            };
+
+            accs.push((*aggregate, acc));
+        }
+
+        Ok(Self {
+            label: schema.label().to_string(),
+            accs,
+        })
+    }
+
+    /// Include a record in the aggregates.
+    pub fn push(&mut self, record: &Record) {
+        for (aggregate, acc) in self.accs.iter_mut() {
+            let field = aggregate
+                .column()
+                .map(|column| &record.args()[column])
+                .filter(|field| !field.is_null());
+
+            match (acc, field) {
+                (Acc::Count(n), _) => *n += 1,
+                (Acc::Int(sum, _), Some(field)) => {
+                    *sum += field
+                        .as_i64()
+                        .map(i128::from)
+                        .or_else(|| field.as_u64().map(i128::from))
+                        .unwrap_or_default()
+                }
+                (Acc::Float(sum, n), Some(field)) => {
+                    *sum += field
+                        .as_f64()
+                        .or_else(|| field.as_i64().map(|i| i as f64))
+                        .or_else(|| field.as_u64().map(|u| u as f64))
+                        .unwrap_or_default();
+                    *n += 1;
+                }
+                (Acc::Min(min), Some(field)) if min.as_ref().is_none_or(|min| field < min) => {
+                    *min = Some(field.clone())
+                }
+                (Acc::Max(max), Some(field)) if max.as_ref().is_none_or(|max| field > max) => {
+                    *max = Some(field.clone())
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the result of each aggregate.
+    /// - `count` is an u64, `sum` is an i64, u64 or f64, and `average` is a f64.
+    /// - `min`, `max` and `average` are `None` if there are no records.
+    /// - Null fields are skipped by aggregates other than `count`.
+    pub fn finish(self) -> Result<Vec<Option<Field>>, Error> {
+        let mut values = Vec::with_capacity(self.accs.len());
+
+        for (aggregate, acc) in self.accs {
+            values.push(match acc {
+                Acc::Count(n) => Some(Field::U64(n)),
+                Acc::Int(sum, unsigned) => {
+                    let field = match unsigned {
+                        true => u64::try_from(sum).ok().map(Field::U64),
+                        false => i64::try_from(sum).ok().map(Field::I64),
+                    };
+                    match field {
+                        Some(field) => Some(field),
+                        None => {
+                            return Err(Error::AggregateOverflow {
+                                label: self.label,
+                                aggregate,
+                            })
+                        }
+                    }
+                }
+                Acc::Float(sum, _) if matches!(aggregate, Aggregate::Sum(_)) => {
+                    Some(Field::F64(sum))
+                }
+                Acc::Float(_, 0) => None,
+                Acc::Float(sum, n) => Some(Field::F64(sum / n as f64)),
+                Acc::Min(field) | Acc::Max(field) => field,
+            });
+        }
+
+        Ok(values)
+    }
+
+    /// Compute aggregates of records grouped by values of columns.
+    /// Groups are ordered by their values.
+    pub fn group<'a>(
+        &self,
+        schema: &SchemaOne,
+        columns: &[usize],
+        records: impl Iterator<Item = &'a Record>,
+    ) -> Result<Vec<Group>, Error> {
+        if let Some(column) = columns.iter().find(|c| **c >= schema.sig().len()) {
+            return Err(Error::UnknownColumn {
+                label: schema.label().to_string(),
+                column: column.to_string(),
+            });
+        }
+
+        let mut groups: BTreeMap<Vec<Field>, Self> = BTreeMap::new();
+        for record in records {
+            let key = columns
+                .iter()
+                .map(|column| record.args()[*column].clone())
+                .collect::<Vec<_>>();
+            groups
+                .entry(key)
+                .or_insert_with(|| self.clone())
+                .push(record);
+        }
+
+        groups
+            .into_iter()
+            .map(|(key, aggregator)| {
+                Ok(Group {
+                    key,
+                    values: aggregator.finish()?,
+                })
+            })
+            .collect()
+    }
+}
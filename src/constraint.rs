@@ -0,0 +1,174 @@
+use std::{
+    fmt::Display,
+    ops::{Bound, RangeBounds},
+};
+
+use crate::{Field, ParseError, SchemaOne, Signature};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// Represents a rule that values of a column must follow, written after the type in a definition.
+///
+/// ```text
+/// perm:   ustr    ustr in {read, write}   u8 1..=100  istr len 1..=32 glob *.example.com
+/// ```
+pub enum Constraint {
+    /// Value is within a range, `1..=100`, `1..`, `..10`.
+    Range(Bound<Field>, Bound<Field>),
+    /// Value is one of a set, `in {read, write}`.
+    In(Vec<Field>),
+    /// Number of characters is within a range, `len 1..=32`.
+    Len(Bound<usize>, Bound<usize>),
+    /// String matches a pattern where `*` is any characters and `?` is one character, `glob *.com`.
+    Glob(String),
+}
+
+impl Constraint {
+    /// Check if field satisfies the constraint, null and patterns always do.
+    pub fn check(&self, field: &Field) -> bool {
+        if field.is_null() || matches!(field, Field::Pattern(_)) {
+            return true;
+        }
+
+        match self {
+            Self::Range(start, end) => (start.as_ref(), end.as_ref()).contains(field),
+            Self::In(values) => values.contains(field),
+            Self::Len(start, end) => match field.as_str() {
+                Some(s) => (*start, *end).contains(&s.chars().count()),
+                None => false,
+            },
+            Self::Glob(pattern) => Self::glob_field(pattern, field),
+        }
+    }
+
+    /// Check if a string field matches a glob, ustr is matched case insensitively.
+    pub(crate) fn glob_field(pattern: &str, field: &Field) -> bool {
+        match field {
+            Field::UStr { lower, .. } => glob(&pattern.to_lowercase(), lower),
+            _ => field.as_str().is_some_and(|s| glob(pattern, s)),
+        }
+    }
+
+    /// Returns true if the word is written as a range, as in `1..=100`.
+    pub(crate) fn is_range(s: &str) -> bool {
+        s.split_once("..")
+            .is_some_and(|(start, _)| !start.contains([':', '=']))
+    }
+
+    /// Parse a range of values of the type.
+    pub(crate) fn range(
+        s: &str,
+        sig: &Signature,
+        location: &str,
+        line: u32,
+    ) -> Result<(Bound<Field>, Bound<Field>), ParseError> {
+        Self::bounds(s, |value| {
+            SchemaOne::parse_value(sig, value, location, line)
+        })
+    }
+
+    /// Parse a range of lengths.
+    pub(crate) fn len(s: &str) -> Result<(Bound<usize>, Bound<usize>), ParseError> {
+        Self::bounds(s, |value| {
+            value.parse().map_err(|_| ParseError::TypeError {
+                target: Signature::U64,
+                value: value.to_string(),
+                reason: "not a length".to_string(),
+            })
+        })
+    }
+
+    fn bounds<T>(
+        s: &str,
+        parse: impl Fn(&str) -> Result<T, ParseError>,
+    ) -> Result<(Bound<T>, Bound<T>), ParseError> {
+        let (start, end) = s.split_once("..").ok_or(ParseError::IllegalArgument)?;
+
+        let start = match start {
+            "" => Bound::Unbounded,
+            start => Bound::Included(parse(start)?),
+        };
+        let end = match end.strip_prefix('=') {
+            Some(end) => Bound::Included(parse(end)?),
+            None if end.is_empty() => Bound::Unbounded,
+            None => Bound::Excluded(parse(end)?),
+        };
+
+        Ok((start, end))
+    }
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Range(start, end) => write_range(
+                f,
+                start.as_ref().map(Field::to_rules_string),
+                end.as_ref().map(Field::to_rules_string),
+            ),
+            Self::In(values) => f.write_fmt(format_args!(
+                "in {{{}}}",
+                values
+                    .iter()
+                    .map(Field::to_rules_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            Self::Len(start, end) => {
+                f.write_str("len ")?;
+                write_range(f, start.map(|n| n.to_string()), end.map(|n| n.to_string()))
+            }
+            Self::Glob(pattern) => {
+                f.write_fmt(format_args!("glob {}", Field::escape_word(pattern)))
+            }
+        }
+    }
+}
+
+pub(crate) fn write_range(
+    f: &mut std::fmt::Formatter<'_>,
+    start: Bound<String>,
+    end: Bound<String>,
+) -> std::fmt::Result {
+    if let Bound::Included(start) = start {
+        f.write_str(&start)?;
+    }
+    f.write_str("..")?;
+    match end {
+        Bound::Included(end) => f.write_fmt(format_args!("={end}")),
+        Bound::Excluded(end) => f.write_str(&end),
+        Bound::Unbounded => Ok(()),
+    }
+}
+
+/// Match a string against a pattern, `*` is any characters and `?` is one character.
+fn glob(pattern: &str, s: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let s = s.chars().collect::<Vec<_>>();
+
+    let (mut p, mut i) = (0, 0);
+    // position of the last `*`, and where it started matching
+    let mut star = None;
+
+    while i < s.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, i));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == s[i] => {
+                p += 1;
+                i += 1;
+            }
+            _ => match star {
+                Some((star_p, star_i)) => {
+                    p = star_p + 1;
+                    i = star_i + 1;
+                    star = Some((star_p, star_i + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
@@ -1,13 +1,22 @@
 use std::{
     collections::HashMap,
+    fs,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
-use crate::{Error, Filter, Pass, Record, RecordSet, Schema};
+use crate::{
+    Aggregate, EnumDef, Error, Field, Filter, Group, Matches, Pass, Plan, Predicate, Query, Record,
+    RecordSet, Schema, SchemaOne, Source,
+};
 
 /// Represents a parsed simplerecords document
 #[derive(Debug)]
-pub struct Document(HashMap<String, RecordSet>);
+pub struct Document {
+    enums: HashMap<String, Arc<EnumDef>>,
+    sets: HashMap<String, RecordSet>,
+    sources: Vec<Source>,
+}
 
 impl Document {
     /// Load a document, uses file path with extension `.rules` if non is specified.
@@ -16,13 +25,62 @@ impl Document {
     /// let doc = Document::load("filename");
     /// ```
     pub fn load(path: impl Into<PathBuf>) -> Result<Self, Error> {
-        let pass = Pass::load(&path.into(), "root", 0, true)?;
-        let (schema, records) = pass.destruct();
+        Self::from_pass(Pass::load(&path.into(), "root", 0, true)?)
+    }
+
+    /// Returns records of the specified type.
+    pub fn get(&self, label: &str) -> Option<&RecordSet> {
+        self.sets.get(label)
+    }
+
+    /// Returns definition of the specified type.
+    pub fn schema(&self, label: &str) -> Option<&SchemaOne> {
+        self.sets.get(label).map(RecordSet::schema)
+    }
 
-        Ok(Self(RecordSet::parse(Schema::parse(schema)?, records)?))
+    /// Returns enum declaration of the specified name.
+    pub fn enum_def(&self, name: &str) -> Option<&EnumDef> {
+        self.enums.get(name).map(Arc::as_ref)
     }
 
-    /// Find and returns the first result matching filter, order is not preserved.
+    /// Parse a filter from text arguments, see `Filter::parse`.
+    pub fn parse_filter(&self, label: &str, args: &[&str]) -> Result<Filter, Error> {
+        match self.schema(label) {
+            Some(schema) => Filter::parse(schema, args),
+            None => Err(Error::NoDefinition {
+                label: label.to_string(),
+            }),
+        }
+    }
+
+    /// Construct a filter from predicates on named columns, see `Filter::named`.
+    pub fn named_filter(&self, label: &str, args: &[(&str, Predicate)]) -> Result<Filter, Error> {
+        match self.schema(label) {
+            Some(schema) => Filter::named(schema, args),
+            None => Err(Error::NoDefinition {
+                label: label.to_string(),
+            }),
+        }
+    }
+
+    /// Parse and run a query, see `Query`.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// # let doc = Options::default().with("whitelist: ustr istr u64").open().unwrap();
+    /// let found = doc.query("whitelist * 127.0.0.1 * | limit 10").unwrap();
+    /// ```
+    pub fn query(&self, s: &str) -> Result<Vec<&Record>, Error> {
+        Query::parse(self, s)?.run(self)
+    }
+
+    /// Returns records of every type, order is not preserved.
+    pub fn record_sets(&self) -> impl Iterator<Item = &RecordSet> {
+        self.sets.values()
+    }
+
+    /// Find and returns the first declared result matching filter.
+    /// - Records are declared in file order, with included files in place of the `include`.
     /// - `None` represents a wildcard.
     /// - Filter length must match rule length.
     ///
@@ -35,7 +93,7 @@ impl Document {
     /// let found = doc.find_one(filter)
     /// ```
     pub fn find_one(&self, filter: Filter) -> Result<Option<&Record>, Error> {
-        match self.0.get(filter.record()) {
+        match self.sets.get(filter.record()) {
             Some(rs) => rs.find_one(filter),
             None => Err(Error::NoDefinition {
                 label: filter.record().to_string(),
@@ -43,7 +101,8 @@ impl Document {
         }
     }
 
-    /// Find and returns all results matching filter, order is not preserved.
+    /// Find and returns all results matching filter, in the order they are declared.
+    /// - Inserted records come after declared records, in the order they were inserted.
     /// - `None` represents a wildcard.
     /// - Filter length must match rule length.
     ///
@@ -56,7 +115,7 @@ impl Document {
     /// let found = doc.find(filter)
     /// ```
     pub fn find(&self, filter: Filter) -> Result<Vec<&Record>, Error> {
-        match self.0.get(filter.record()) {
+        match self.sets.get(filter.record()) {
             Some(rs) => rs.find(filter),
             None => Err(Error::NoDefinition {
                 label: filter.record().to_string(),
@@ -65,12 +124,579 @@ impl Document {
     }
 }
 
+impl Document {
+    /// Find and returns results matching filter that are valid at the instant, in the order they are declared.
+    /// - Validity is bounded by columns marked `not_before`, `not_after` or `expires`.
+    /// - The instant is a date, datetime or seconds since epoch.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// # let doc = Options::default()
+    /// #     .with("whitelist: ustr istr expiry:date expires")
+    /// #     .with("whitelist joe 127.0.0.1 2025-01-01")
+    /// #     .open()
+    /// #     .unwrap();
+    /// let valid = doc
+    ///     .find_at(filter!("whitelist"; "joe", *, *), &Field::now())
+    ///     .unwrap();
+    /// ```
+    pub fn find_at(&self, filter: Filter, instant: &Field) -> Result<Vec<&Record>, Error> {
+        match self.sets.get(filter.record()) {
+            Some(rs) => rs.find_at(filter, instant),
+            None => Err(Error::NoDefinition {
+                label: filter.record().to_string(),
+            }),
+        }
+    }
+
+    /// Returns records of the type with patterns matching all values, most specific first, see `RecordSet::match_rules`.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// # let doc = Options::default()
+    /// #     .with("acl: host:istr pattern port:u16 pattern action:istr")
+    /// #     .with("acl *.example.com 443 allow")
+    /// #     .with("acl * 1000..2000 deny")
+    /// #     .open()
+    /// #     .unwrap();
+    /// let rules = doc
+    ///     .match_rules("acl", &[Field::from("www.example.com"), Field::U16(443)])
+    ///     .unwrap();
+    /// assert_eq!(rules[0].args()[2], Field::from("allow"));
+    /// ```
+    pub fn match_rules(&self, label: &str, values: &[Field]) -> Result<Vec<&Record>, Error> {
+        match self.sets.get(label) {
+            Some(rs) => rs.match_rules(values),
+            None => Err(Error::NoDefinition {
+                label: label.to_string(),
+            }),
+        }
+    }
+
+    /// Returns records of every type past their `not_after` or `expires` bounds, ordered by type then declaration.
+    pub fn expired(&self, now: &Field) -> Result<Vec<&Record>, Error> {
+        let mut labels = self.sets.keys().collect::<Vec<_>>();
+        labels.sort();
+
+        let mut out = Vec::new();
+        for label in labels {
+            out.extend(self.sets[label].expired(now)?);
+        }
+        Ok(out)
+    }
+}
+
+impl Document {
+    /// Returns a lazy iterator over records matching filter, see `RecordSet::iter_matches`.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// # let doc = Options::default().with("whitelist: ustr istr u64").open().unwrap();
+    /// let any_local = doc
+    ///     .iter_matches(filter!("whitelist"; *, "127.0.0.1", *))
+    ///     .unwrap()
+    ///     .next()
+    ///     .is_some();
+    /// ```
+    pub fn iter_matches(&self, filter: Filter) -> Result<Matches<'_>, Error> {
+        match self.sets.get(filter.record()) {
+            Some(rs) => rs.iter_matches(filter),
+            None => Err(Error::NoDefinition {
+                label: filter.record().to_string(),
+            }),
+        }
+    }
+}
+
+impl Document {
+    /// Returns number of records matching filter, without collecting them.
+    pub fn count(&self, filter: Filter) -> Result<usize, Error> {
+        match self.sets.get(filter.record()) {
+            Some(rs) => rs.count(filter),
+            None => Err(Error::NoDefinition {
+                label: filter.record().to_string(),
+            }),
+        }
+    }
+
+    /// Computes aggregates over records matching filter.
+    /// - `count` is an u64, `sum` is an i64, u64 or f64, and `average` is a f64.
+    /// - `min`, `max` and `average` are `None` if no records match.
+    /// - Null fields are skipped by aggregates other than `count`.
+    /// - Only `count` is computed over `pattern` columns.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// # let doc = Options::default()
+    /// #     .with("whitelist: ustr istr u64")
+    /// #     .with("whitelist joe 127.0.0.1 10")
+    /// #     .with("whitelist bob 127.0.0.2 20")
+    /// #     .open()
+    /// #     .unwrap();
+    /// let values = doc
+    ///     .aggregate(filter!("whitelist"; *, *, *), &[Aggregate::Count, Aggregate::Sum(2)])
+    ///     .unwrap();
+    /// assert_eq!(values, vec![Some(Field::U64(2)), Some(Field::U64(30))]);
+    /// ```
+    pub fn aggregate(
+        &self,
+        filter: Filter,
+        aggregates: &[Aggregate],
+    ) -> Result<Vec<Option<Field>>, Error> {
+        match self.sets.get(filter.record()) {
+            Some(rs) => rs.aggregate(filter, aggregates),
+            None => Err(Error::NoDefinition {
+                label: filter.record().to_string(),
+            }),
+        }
+    }
+
+    /// Computes aggregates over records matching filter, grouped by values of columns.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// # let doc = Options::default()
+    /// #     .with("whitelist: ustr istr u64")
+    /// #     .with("whitelist joe 127.0.0.1 10")
+    /// #     .with("whitelist joe 127.0.0.2 20")
+    /// #     .open()
+    /// #     .unwrap();
+    /// // number of whitelisted ips per user
+    /// let groups = doc
+    ///     .group_by(filter!("whitelist"; *, *, *), &[0], &[Aggregate::Count])
+    ///     .unwrap();
+    /// assert_eq!(groups[0].values(), &[Some(Field::U64(2))]);
+    /// ```
+    pub fn group_by(
+        &self,
+        filter: Filter,
+        columns: &[usize],
+        aggregates: &[Aggregate],
+    ) -> Result<Vec<Group>, Error> {
+        match self.sets.get(filter.record()) {
+            Some(rs) => rs.group_by(filter, columns, aggregates),
+            None => Err(Error::NoDefinition {
+                label: filter.record().to_string(),
+            }),
+        }
+    }
+}
+
+impl Document {
+    /// Returns values of columns for each record matching filter.
+    pub fn project(&self, filter: Filter, columns: &[usize]) -> Result<Vec<Vec<Field>>, Error> {
+        match self.sets.get(filter.record()) {
+            Some(rs) => rs.project(filter, columns),
+            None => Err(Error::NoDefinition {
+                label: filter.record().to_string(),
+            }),
+        }
+    }
+
+    /// Returns distinct values of columns among records matching filter, see `RecordSet::distinct`.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// # let doc = Options::default()
+    /// #     .with("whitelist: ustr istr u64")
+    /// #     .with("whitelist joe 127.0.0.1 10")
+    /// #     .with("whitelist bob 127.0.0.1 20")
+    /// #     .open()
+    /// #     .unwrap();
+    /// let ips = doc.distinct(filter!("whitelist"; *, *, *), &[1]).unwrap();
+    /// assert_eq!(ips, vec![vec![Field::from("127.0.0.1")]]);
+    /// ```
+    pub fn distinct(&self, filter: Filter, columns: &[usize]) -> Result<Vec<Vec<Field>>, Error> {
+        match self.sets.get(filter.record()) {
+            Some(rs) => rs.distinct(filter, columns),
+            None => Err(Error::NoDefinition {
+                label: filter.record().to_string(),
+            }),
+        }
+    }
+}
+
+impl Document {
+    /// Pairs records of two types with equal values in a column of each, see `RecordSet::join`.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// # let doc = Options::default()
+    /// #     .with("user: ustr u8")
+    /// #     .with("perm: ustr istr bool")
+    /// #     .with("user joe 3")
+    /// #     .with("perm joe write true")
+    /// #     .open()
+    /// #     .unwrap();
+    /// // permissions of users from level 3
+    /// let pairs = doc
+    ///     .join(filter!("user"; *, >= 3), 0, filter!("perm"; *, *, *), 0)
+    ///     .unwrap();
+    /// assert_eq!(pairs[0].1.args()[1], Field::from("write"));
+    /// ```
+    pub fn join(
+        &self,
+        filter: Filter,
+        column: usize,
+        other_filter: Filter,
+        other_column: usize,
+    ) -> Result<Vec<(&Record, &Record)>, Error> {
+        let set = |label: &str| {
+            self.sets.get(label).ok_or(Error::NoDefinition {
+                label: label.to_string(),
+            })
+        };
+
+        set(filter.record())?.join(
+            filter,
+            column,
+            set(other_filter.record())?,
+            other_filter,
+            other_column,
+        )
+    }
+}
+
+impl Document {
+    /// Adds a secondary index on a column of the specified type.
+    ///
+    /// Columns can also be indexed in the definition, `whitelist: ustr istr indexed u64`.
+    pub fn create_index(&mut self, label: &str, column: usize) -> Result<(), Error> {
+        match self.sets.get_mut(label) {
+            Some(rs) => rs.create_index(column),
+            None => Err(Error::NoDefinition {
+                label: label.to_string(),
+            }),
+        }
+    }
+
+    /// Removes the secondary index on a column of the specified type.
+    pub fn drop_index(&mut self, label: &str, column: usize) -> Result<(), Error> {
+        match self.sets.get_mut(label) {
+            Some(rs) => rs.drop_index(column),
+            None => Err(Error::NoDefinition {
+                label: label.to_string(),
+            }),
+        }
+    }
+
+    /// Returns how records matching filter would be found.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// # let doc = Options::default()
+    /// #     .with("whitelist: ustr istr indexed u64")
+    /// #     .with("whitelist joe 127.0.0.1 123456")
+    /// #     .with("whitelist bob 127.0.0.2 123457")
+    /// #     .open()
+    /// #     .unwrap();
+    /// let plan = doc.explain(filter!("whitelist"; *, "127.0.0.1", *)).unwrap();
+    /// assert_eq!(plan, Plan::Index { column: 1, estimate: 1 });
+    /// ```
+    pub fn explain(&self, filter: Filter) -> Result<Plan, Error> {
+        match self.sets.get(filter.record()) {
+            Some(rs) => rs.explain(filter),
+            None => Err(Error::NoDefinition {
+                label: filter.record().to_string(),
+            }),
+        }
+    }
+}
+
+impl Document {
+    /// Insert a new record, fields are checked against the record definition.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// # let mut doc = Options::default().with("user: ustr u8").open().unwrap();
+    /// doc.insert("user", vec![Field::IStr("joe".to_string()), Field::U8(17)])
+    ///     .unwrap();
+    /// ```
+    pub fn insert(&mut self, label: &str, fields: Vec<Field>) -> Result<(), Error> {
+        match self.sets.get_mut(label) {
+            Some(rs) => rs.insert(fields),
+            None => Err(Error::NoDefinition {
+                label: label.to_string(),
+            }),
+        }
+    }
+
+    /// Remove and returns all records matching filter.
+    pub fn remove(&mut self, filter: Filter) -> Result<Vec<Record>, Error> {
+        match self.sets.get_mut(filter.record()) {
+            Some(rs) => rs.remove(filter),
+            None => Err(Error::NoDefinition {
+                label: filter.record().to_string(),
+            }),
+        }
+    }
+
+    /// Amend all records matching filter, returns the number of records changed.
+    /// - `None` leaves the field unchanged.
+    /// - Changes length must match rule length.
+    ///
+    /// Nothing is changed if any amended record would duplicate another.
+    pub fn update(&mut self, filter: Filter, changes: &[Option<Field>]) -> Result<usize, Error> {
+        match self.sets.get_mut(filter.record()) {
+            Some(rs) => rs.update(filter, changes),
+            None => Err(Error::NoDefinition {
+                label: filter.record().to_string(),
+            }),
+        }
+    }
+}
+
 impl Document {
     /// Create a document by providing a string, and parse it as if it were from the specified path.
     pub fn load_as(content: &str, path: &Path) -> Result<Self, Error> {
-        let pass = Pass::parse(path, content)?;
-        let (schema, records) = pass.destruct();
+        Self::from_pass(Pass::parse(path, content)?)
+    }
+
+    fn from_pass(pass: Pass) -> Result<Self, Error> {
+        let sources = pass.sources().to_vec();
+        let (enums, schema, records) = pass.destruct_quoted();
+        let enums = EnumDef::parse(enums)?;
 
-        Ok(Self(RecordSet::parse(Schema::parse(schema)?, records)?))
+        Ok(Self {
+            sets: RecordSet::parse_quoted(Schema::parse(schema, &enums)?, records)?,
+            enums,
+            sources,
+        })
     }
 }
+
+impl Document {
+    /// Returns files read while loading, the first one is the root file.
+    pub fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    /// Returns new content of each file with changed records, comments and layout are preserved.
+    /// - Amended records are changed in place.
+    /// - Removed records are taken out of their lines.
+    /// - Inserted records are written to the file and scope of their definition.
+    pub fn rewrite(&self) -> Result<Vec<(&Source, String)>, Error> {
+        let mut changed = Vec::new();
+
+        for source in self.sources.iter() {
+            let content = source.rewrite(self)?;
+            if content != source.content() {
+                changed.push((source, content));
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// Write changes back to the files they were loaded from, then reload the document.
+    /// - Content not read from disk, such as by `load_as`, is only changed in memory.
+    pub fn save(&mut self) -> Result<(), Error> {
+        let mut root = self.sources[0].content().to_string();
+
+        for (source, content) in self.rewrite()? {
+            if source.on_disk() {
+                if let Err(e) = fs::write(source.path(), &content) {
+                    return Err(Error::IoError {
+                        reason: e.to_string(),
+                    });
+                }
+            }
+
+            if source == &self.sources[0] {
+                root = content;
+            }
+        }
+
+        let path = self.sources[0].path().clone();
+        *self = if self.sources[0].on_disk() {
+            Self::from_pass(Pass::load(&path, "root", 0, true)?)?
+        } else {
+            Self::from_pass(Pass::parse(&path, &root)?)?
+        };
+
+        Ok(())
+    }
+}
+
+impl Document {
+    /// Serialize document to rules, included files are written inline.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// # use std::path::Path;
+    /// # let doc = Options::default().with("user: ustr u8").with("user joe 17").open().unwrap();
+    /// let copy = Document::load_as(&doc.to_rules_string(), Path::new("copy.rules")).unwrap();
+    /// ```
+    pub fn to_rules_string(&self) -> String {
+        Self::write_rows(self.sorted_rows())
+    }
+
+    /// Serialize document to one rules file for each location, returns `(file name, content)` pairs.
+    /// - The file named `root` is listed first and includes all other files.
+    /// - Each `include` is written where the first row of the file is, so declaration order is kept.
+    pub fn to_rules_files(&self, root: &str) -> Vec<(String, String)> {
+        let mut files: Vec<(&str, Vec<Row>)> = vec![(root, Vec::new())];
+        for row in self.sorted_rows() {
+            let file = Pass::split_location(row.location).0;
+            let index = match files.iter().position(|(name, _)| *name == file) {
+                Some(index) => index,
+                None => {
+                    let location = files[0].1.last().map_or("", |row| row.location);
+                    files[0].1.push(Row {
+                        location,
+                        line: 0,
+                        label: "include",
+                        cells: vec!["include".to_string(), Field::escape_word(file)],
+                    });
+                    files.push((file, Vec::new()));
+                    files.len() - 1
+                }
+            };
+            files[index].1.push(row);
+        }
+
+        files
+            .into_iter()
+            .map(|(file, rows)| (Self::file_name(file), Self::write_rows(rows)))
+            .collect()
+    }
+
+    fn file_name(file: &str) -> String {
+        if Path::new(file).extension().is_some() {
+            file.to_string()
+        } else {
+            format!("{file}.rules")
+        }
+    }
+
+    fn rows(&self) -> Vec<Row<'_>> {
+        let mut rows = Vec::new();
+
+        for def in self.enums.values() {
+            rows.push(Row {
+                location: def.location(),
+                line: def.line(),
+                label: "enum",
+                cells: ["enum", def.name(), "="]
+                    .into_iter()
+                    .chain(def.variants().iter().map(String::as_str))
+                    .map(Field::escape_word)
+                    .collect(),
+            });
+        }
+
+        for rs in self.sets.values() {
+            let schema = rs.schema();
+            rows.push(Row {
+                location: schema.location(),
+                line: schema.line(),
+                label: schema.label(),
+                cells: std::iter::once(format!("{}:", schema.label()))
+                    .chain(schema.column_strings())
+                    .collect(),
+            });
+
+            for record in rs.records() {
+                rows.push(Row {
+                    location: record.location(),
+                    line: record.line(),
+                    label: record.label(),
+                    cells: std::iter::once(record.label().to_string())
+                        .chain(record.args().iter().map(Field::to_rules_string))
+                        .collect(),
+                })
+            }
+        }
+
+        rows
+    }
+
+    /// Returns rows in declaration order, with rows of included files in place of the `include`.
+    fn sorted_rows(&self) -> Vec<Row<'_>> {
+        let mut rows = self.rows();
+        rows.sort_by_cached_key(|row| {
+            (
+                self.position(row.location, row.line),
+                Pass::split_location(row.location).0.to_string(),
+                row.cells.clone(),
+            )
+        });
+        rows
+    }
+
+    /// Returns lines of each `include` leading to a row from the root file, then the line of the row.
+    /// - Rows inserted at runtime have no line number, they come last in their file.
+    /// - Rows of files not read come after all others.
+    fn position(&self, location: &str, line: u32) -> Vec<(bool, u32)> {
+        let mut position = vec![(line == 0, line)];
+        let mut file = Pass::split_location(location).0;
+
+        for _ in 0..self.sources.len() {
+            match self.sources.iter().find(|source| source.name() == file) {
+                Some(source) => match source.included_at() {
+                    Some((parent, line)) => {
+                        position.push((false, line));
+                        file = parent;
+                    }
+                    None => break,
+                },
+                None => {
+                    position.push((true, u32::MAX));
+                    break;
+                }
+            }
+        }
+
+        position.reverse();
+        position
+    }
+
+    /// Write rows in order, aligning the columns of each record type.
+    fn write_rows(rows: Vec<Row>) -> String {
+        let mut widths: HashMap<&str, Vec<usize>> = HashMap::new();
+        for row in rows.iter() {
+            let width = widths.entry(row.label).or_default();
+            for (i, cell) in row.cells.iter().enumerate() {
+                match width.get_mut(i) {
+                    Some(w) => *w = (*w).max(cell.chars().count()),
+                    None => width.push(cell.chars().count()),
+                }
+            }
+        }
+
+        let mut out = String::new();
+        let mut scope = None;
+
+        for row in rows.iter() {
+            let row_scope = Pass::split_location(row.location).1;
+            if row_scope != scope {
+                match row_scope {
+                    Some(s) => out.push_str(&format!("scope {}\n", Field::escape_word(s))),
+                    None => out.push_str("scope\n"),
+                }
+                scope = row_scope;
+            }
+
+            let width = &widths[row.label];
+            let line = row
+                .cells
+                .iter()
+                .zip(width.iter())
+                .map(|(cell, w)| format!("{cell:w$}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+/// A definition or record as written in a rules file.
+struct Row<'a> {
+    location: &'a str,
+    line: u32,
+    label: &'a str,
+    cells: Vec<String>,
+}
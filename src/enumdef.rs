@@ -0,0 +1,120 @@
+use std::{collections::HashMap, fmt::Display, str::FromStr, sync::Arc};
+
+use crate::{Error, Field, ParseError, Signature};
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+/// Represents an enum declaration, a type with a fixed list of values ordered as declared.
+///
+/// ```text
+/// enum level = guest member admin
+/// user:   ustr    level
+/// user    joe     admin
+/// ```
+pub struct EnumDef {
+    name: String,
+    location: String,
+    line: u32,
+    variants: Vec<String>,
+}
+
+impl EnumDef {
+    /// Returns name of the type.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns location file.
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+
+    /// Returns location line number.
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Returns values in declaration order.
+    pub fn variants(&self) -> &[String] {
+        &self.variants
+    }
+
+    /// Returns position of the value in declaration order.
+    pub fn position(&self, variant: &str) -> Option<usize> {
+        self.variants.iter().position(|v| v == variant)
+    }
+
+    /// Converts declaration to how it is written in a rules file.
+    pub fn to_rules_string(&self) -> String {
+        format!(
+            "enum {} = {}",
+            Field::escape_word(&self.name),
+            self.variants
+                .iter()
+                .map(|v| Field::escape_word(v))
+                .collect::<Vec<_>>()
+                .join(" ")
+        )
+    }
+
+    /// Parse enum stream into declarations by name.
+    pub fn parse(
+        entries: Vec<(String, u32, String, Vec<String>)>,
+    ) -> Result<HashMap<String, Arc<Self>>, Error> {
+        let mut parsed: HashMap<String, Arc<Self>> = HashMap::with_capacity(entries.len());
+
+        for (location, line, name, variants) in entries {
+            let error = |reason| Error::ParseError {
+                location: location.clone(),
+                line,
+                reason,
+            };
+
+            if name == "include" || name == "enum" || Signature::from_str(&name).is_ok() {
+                return Err(error(ParseError::IllegalName { label: name }));
+            }
+            if let Some(first) = parsed.get(&name) {
+                return Err(error(ParseError::DuplicatedDefinition {
+                    first_appear: first.location.clone(),
+                    line: first.line,
+                    label: name,
+                }));
+            }
+            if variants.is_empty() {
+                return Err(error(ParseError::MissingArguments));
+            }
+            if let Some(variant) = variants
+                .iter()
+                .enumerate()
+                .find(|(i, variant)| variants[..*i].contains(variant))
+                .map(|(_, variant)| variant)
+            {
+                return Err(error(ParseError::DuplicatedVariant {
+                    variant: variant.clone(),
+                }));
+            }
+
+            parsed.insert(
+                name.clone(),
+                Arc::new(Self {
+                    name,
+                    location,
+                    line,
+                    variants,
+                }),
+            );
+        }
+
+        Ok(parsed)
+    }
+}
+
+impl Display for EnumDef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "({}@{}) {}",
+            self.location,
+            self.line,
+            self.to_rules_string()
+        ))
+    }
+}
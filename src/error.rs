@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use crate::{Filter, SchemaOne, Signature};
+use crate::{Aggregate, Constraint, Field, Filter, SchemaOne, Signature};
 
 #[derive(Debug, PartialEq, Eq)]
 /// Represents all possible errors that can occur.
@@ -18,10 +18,49 @@ pub enum Error {
     /// Filter does not match record definition.
     FilterMismatch {
         expected: Box<SchemaOne>,
-        got: Filter,
+        got: Box<Filter>,
     },
     /// No definition for specified record type.
     NoDefinition { label: String },
+    /// Record fields do not match record definition.
+    RecordMismatch {
+        expected: Box<SchemaOne>,
+        got: Vec<Field>,
+    },
+    /// Two entries with exactly the same values is not allowed
+    DuplicatedEntry {
+        first_appear: String,
+        line: u32,
+        label: String,
+    },
+    /// No column with the specified index in record definition.
+    UnknownColumn { label: String, column: String },
+    /// Error when parsing a query, column counts characters from 1.
+    QueryError { column: usize, reason: QueryError },
+    /// Aggregate cannot be computed on a column of this type.
+    AggregateMismatch {
+        label: String,
+        aggregate: Aggregate,
+        got: Signature,
+    },
+    /// Aggregate result does not fit in its type.
+    AggregateOverflow { label: String, aggregate: Aggregate },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// Represents all possible errors that can occur while parsing a query.
+pub enum QueryError {
+    /// Argument or value cannot be parsed.
+    ParseError(ParseError),
+    /// Query does not continue as expected, `None` for end of query.
+    Expected {
+        expected: String,
+        got: Option<String>,
+    },
+    /// No column with the specified index.
+    UnknownColumn { got: String },
+    /// Unrecognised stage after `|`.
+    UnknownStage { got: String },
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -29,7 +68,7 @@ pub enum Error {
 pub enum ParseError {
     /// Too few arguments for commands
     MissingArguments,
-    /// Illegal record type name (cannot be `include`)
+    /// Illegal record type or enum name (cannot be `include`, `enum` or a built in type)
     IllegalName { label: String },
     /// Two entries with exactly the same values is not allowed
     DuplicatedEntry {
@@ -45,6 +84,20 @@ pub enum ParseError {
     },
     /// Unrecognised data type in type definition
     UnrecognisedType { got: String },
+    /// Column name is empty or a number
+    IllegalColumnName { name: String },
+    /// Two columns with the same name in a type definition
+    DuplicatedColumn { name: String },
+    /// Constraint cannot be read, or does not apply to the type
+    IllegalConstraint { got: String },
+    /// Value does not satisfy a constraint of its column
+    ConstraintViolation {
+        column: usize,
+        constraint: Box<Constraint>,
+        value: String,
+    },
+    /// Same value listed twice in an enum declaration
+    DuplicatedVariant { variant: String },
     /// String is not closed at EOL
     UnclosedString,
     /// Multi-line comment is not closed at EOF
@@ -85,6 +138,54 @@ impl Display for Error {
             Self::NoDefinition { label } => {
                 f.write_fmt(format_args!("no definition for `{label}`"))
             }
+            Self::RecordMismatch { expected, got } => f.write_fmt(format_args!(
+                "record mismatch: expected {expected}, got {}",
+                got.iter()
+                    .map(Field::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )),
+            Self::DuplicatedEntry {
+                first_appear,
+                line,
+                label,
+            } => f.write_fmt(format_args!(
+                "duplicated entry with {first_appear}@{line} for `{label}`"
+            )),
+            Self::UnknownColumn { label, column } => {
+                f.write_fmt(format_args!("unknown column `{column}` for `{label}`"))
+            }
+            Self::QueryError { column, reason } => {
+                f.write_fmt(format_args!("query error at column {column}: {reason}"))
+            }
+            Self::AggregateMismatch {
+                label,
+                aggregate,
+                got,
+            } => f.write_fmt(format_args!(
+                "cannot compute `{aggregate}` over {got} for `{label}`"
+            )),
+            Self::AggregateOverflow { label, aggregate } => {
+                f.write_fmt(format_args!("`{aggregate}` overflowed for `{label}`"))
+            }
+        }
+    }
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ParseError(reason) => reason.fmt(f),
+            Self::Expected {
+                expected,
+                got: Some(got),
+            } => f.write_fmt(format_args!("expected {expected}, got `{got}`")),
+            Self::Expected {
+                expected,
+                got: None,
+            } => f.write_fmt(format_args!("expected {expected}, got end of query")),
+            Self::UnknownColumn { got } => f.write_fmt(format_args!("unknown column `{got}`")),
+            Self::UnknownStage { got } => f.write_fmt(format_args!("unknown stage `{got}`")),
         }
     }
 }
@@ -111,6 +212,25 @@ impl Display for ParseError {
             Self::UnrecognisedType { got } => {
                 f.write_fmt(format_args!("unrecognised type `{got}`"))
             }
+            Self::IllegalColumnName { name } => {
+                f.write_fmt(format_args!("illegal column name `{name}`"))
+            }
+            Self::DuplicatedColumn { name } => {
+                f.write_fmt(format_args!("duplicated column `{name}`"))
+            }
+            Self::IllegalConstraint { got } => {
+                f.write_fmt(format_args!("illegal constraint `{got}`"))
+            }
+            Self::ConstraintViolation {
+                column,
+                constraint,
+                value,
+            } => f.write_fmt(format_args!(
+                "`{value}` in column {column} violates `{constraint}`"
+            )),
+            Self::DuplicatedVariant { variant } => {
+                f.write_fmt(format_args!("duplicated enum value `{variant}`"))
+            }
             Self::UnclosedString => f.write_str("unclosed string"),
             Self::UnclosedMultiLineComment => f.write_str("unclosed multi-line comment"),
             Self::IllegalArgument => f.write_str("illegal argument"),
@@ -1,6 +1,16 @@
-use std::{fmt::Display, hash::Hash};
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    hash::Hash,
+    net::IpAddr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use crate::Signature;
+use crate::{time, EnumDef, Pattern, Signature};
+
+/// Null to borrow from, as in ranges starting after null.
+pub(crate) static NULL: Field = Field::Null;
 
 #[derive(Debug, Clone)]
 /// Represents a typed field in a record.
@@ -41,6 +51,51 @@ pub enum Field {
     U64(u64),
     /// Boolean value
     Bool(bool),
+    /// Value of an enum, stored as its position in the declaration
+    ///
+    /// ```text
+    /// enum level = guest member admin
+    /// ```
+    Enum(Arc<EnumDef>, usize),
+    /// List of values of the same type
+    ///
+    /// ```text
+    /// ports:  istr    [u16]
+    /// ports   web     [80,443]
+    /// ```
+    List(Vec<Field>),
+    /// IP address, for ip, ipv4 and ipv6 columns
+    Ip(IpAddr),
+    /// Network of addresses, as in `10.0.0.0/16`, bits after the prefix are always cleared
+    Cidr(IpAddr, u8),
+    /// Calendar date, stored as days since 1970-01-01
+    ///
+    /// ```text
+    /// 2024-02-29
+    /// ```
+    Date(i64),
+    /// Instant as RFC 3339, stored as seconds since epoch and the offset it was written in
+    ///
+    /// ```text
+    /// "2024-02-29T12:00:00+08:00"
+    /// ```
+    DateTime { secs: i64, nanos: u32, offset: i16 },
+    /// Length of time in seconds, written in units as in `30d` or `1h30m`
+    Duration(i64),
+    /// Value matching many values, only in columns declared with `pattern`
+    ///
+    /// ```text
+    /// acl:    host:istr pattern   port:u16 pattern
+    /// acl     *.example.com       1000..2000
+    /// ```
+    Pattern(Box<Pattern>),
+    /// No value, only in nullable columns
+    ///
+    /// ```text
+    /// whitelist:  ustr    istr    u64?
+    /// whitelist   joe     1.1.1.1 null
+    /// ```
+    Null,
 }
 
 impl Field {
@@ -58,16 +113,63 @@ impl Field {
                 acc
             })
     }
+
+    /// Write a string as a bare word, or quote it if it would not be read back as is.
+    /// - `null` is quoted so it is not read back as null.
+    pub fn escape_word(s: &str) -> String {
+        if s.is_empty()
+            || s == "null"
+            || s.contains("/*")
+            || s.chars()
+                .any(|c| c.is_whitespace() || matches!(c, '\\' | ':' | '#' | '\'' | '"' | '`'))
+        {
+            format!("\"{}\"", Self::escape_str('"', s))
+        } else {
+            s.to_string()
+        }
+    }
+
+    /// Converts field to how it is written in a rules file.
+    pub fn to_rules_string(&self) -> String {
+        match self {
+            Self::IStr(s) | Self::UStr { original: s, .. } => Self::escape_word(s),
+            Self::Char(c) => Self::escape_word(&c.to_string()),
+            Self::Enum(def, i) => Self::escape_word(&def.variants()[*i]),
+            Self::List(items) => Self::escape_word(&Self::list_string(items)),
+            Self::Ip(_) | Self::Cidr(..) | Self::DateTime { .. } | Self::Pattern(_) => {
+                Self::escape_word(&self.to_string())
+            }
+            _ => self.to_string(),
+        }
+    }
+
+    /// Write list as `[a,b]`, with backslashes and commas in elements escaped.
+    fn list_string(items: &[Field]) -> String {
+        let items = items
+            .iter()
+            .map(|item| {
+                let s = match item {
+                    Self::IStr(s) | Self::UStr { original: s, .. } => s.clone(),
+                    Self::Char(c) => c.to_string(),
+                    _ => item.to_string(),
+                };
+                s.replace('\\', "\\\\").replace(',', "\\,")
+            })
+            .collect::<Vec<_>>();
+        format!("[{}]", items.join(","))
+    }
 }
 
 impl Display for Field {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::IStr(s) => f.write_fmt(format_args!("\"{}\"", Self::escape_str('\'', s))),
+            Self::IStr(s) => f.write_fmt(format_args!("\"{}\"", Self::escape_str('"', s))),
             Self::UStr { original, .. } => {
-                f.write_fmt(format_args!("\"{}\"", Self::escape_str('\'', original)))
+                f.write_fmt(format_args!("\"{}\"", Self::escape_str('"', original)))
+            }
+            Self::Char(c) => {
+                f.write_fmt(format_args!("'{}'", Self::escape_str('\'', &c.to_string())))
             }
-            Self::Char(c) => f.write_fmt(format_args!("'{c}'")),
             Self::F32(n) => f.write_fmt(format_args!("{n}")),
             Self::F64(n) => f.write_fmt(format_args!("{n}")),
             Self::I8(i) => f.write_fmt(format_args!("{i}")),
@@ -79,6 +181,34 @@ impl Display for Field {
             Self::U32(u) => f.write_fmt(format_args!("{u}")),
             Self::U64(u) => f.write_fmt(format_args!("{u}")),
             Self::Bool(b) => f.write_fmt(format_args!("{b}")),
+            Self::Enum(def, i) => f.write_str(&def.variants()[*i]),
+            Self::List(_) => f.write_str(&self.to_rules_string()),
+            Self::Ip(addr) => f.write_fmt(format_args!("{addr}")),
+            Self::Cidr(addr, len) => f.write_fmt(format_args!("{addr}/{len}")),
+            Self::Date(days) => f.write_str(&time::format_date(*days)),
+            Self::DateTime {
+                secs,
+                nanos,
+                offset,
+            } => f.write_str(&time::format_datetime(*secs, *nanos, *offset)),
+            Self::Duration(secs) => f.write_str(&time::format_duration(*secs)),
+            Self::Pattern(pattern) => pattern.fmt(f),
+            Self::Null => f.write_str("null"),
+        }
+    }
+}
+
+impl Field {
+    /// Returns the current time as a datetime in UTC.
+    pub fn now() -> Self {
+        let since = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as i64,
+            Err(before) => -(before.duration().as_secs() as i64),
+        };
+        Self::DateTime {
+            secs: since,
+            nanos: 0,
+            offset: 0,
         }
     }
 }
@@ -200,6 +330,71 @@ impl Field {
             _ => None,
         }
     }
+
+    /// Converts list to its elements
+    pub fn as_list(&self) -> Option<&[Field]> {
+        match self {
+            Self::List(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Converts ip to address
+    pub fn as_ip(&self) -> Option<IpAddr> {
+        match self {
+            Self::Ip(addr) => Some(*addr),
+            _ => None,
+        }
+    }
+
+    /// Converts cidr to network address and prefix length
+    pub fn as_cidr(&self) -> Option<(IpAddr, u8)> {
+        match self {
+            Self::Cidr(addr, len) => Some((*addr, *len)),
+            _ => None,
+        }
+    }
+
+    /// Converts date to days since 1970-01-01
+    pub fn as_date(&self) -> Option<i64> {
+        match self {
+            Self::Date(days) => Some(*days),
+            _ => None,
+        }
+    }
+
+    /// Converts date or datetime to seconds since epoch, dates are at midnight UTC
+    pub fn as_timestamp(&self) -> Option<i64> {
+        match self {
+            Self::Date(days) => Some(days * 86400),
+            Self::DateTime { secs, .. } => Some(*secs),
+            _ => None,
+        }
+    }
+
+    /// Converts duration to seconds
+    pub fn as_duration(&self) -> Option<i64> {
+        match self {
+            Self::Duration(secs) => Some(*secs),
+            _ => None,
+        }
+    }
+
+    /// Converts pattern to pattern
+    pub fn as_pattern(&self) -> Option<&Pattern> {
+        match self {
+            Self::Pattern(pattern) => Some(pattern),
+            _ => None,
+        }
+    }
+
+    /// Converts enum to its value name
+    pub fn as_variant(&self) -> Option<&str> {
+        match self {
+            Self::Enum(def, i) => Some(&def.variants()[*i]),
+            _ => None,
+        }
+    }
 }
 
 impl Field {
@@ -207,10 +402,14 @@ impl Field {
     /// - f32 -> f64
     /// - i8, i16, i32 -> i64
     /// - u8, u16, u32 -> u64
-    /// - ustr -> istr
+    /// - lists upcast each element
     pub fn upcast(&self) -> Self {
         match self {
-            Self::UStr { original, .. } | Self::IStr(original) => Self::IStr(original.clone()),
+            Self::IStr(s) => Self::IStr(s.clone()),
+            Self::UStr { original, lower } => Self::UStr {
+                original: original.clone(),
+                lower: lower.clone(),
+            },
             Self::Char(c) => Self::Char(*c),
             Self::F32(f) => Self::F64(*f as f64),
             Self::F64(f) => Self::F64(*f),
@@ -223,12 +422,44 @@ impl Field {
             Self::U32(u) => Self::U64(*u as u64),
             Self::U64(u) => Self::U64(*u),
             Self::Bool(b) => Self::Bool(*b),
+            Self::Enum(def, i) => Self::Enum(def.clone(), *i),
+            Self::List(items) => Self::List(items.iter().map(Self::upcast).collect()),
+            Self::Ip(addr) => Self::Ip(*addr),
+            Self::Cidr(addr, len) => Self::Cidr(*addr, *len),
+            Self::Date(days) => Self::Date(*days),
+            Self::DateTime {
+                secs,
+                nanos,
+                offset,
+            } => Self::DateTime {
+                secs: *secs,
+                nanos: *nanos,
+                offset: *offset,
+            },
+            Self::Duration(secs) => Self::Duration(*secs),
+            Self::Pattern(pattern) => Self::Pattern(Box::new(pattern.upcast())),
+            Self::Null => Self::Null,
         }
     }
 
+    /// Returns true if field is null.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
     /// Converts type value to type signature.
+    /// - Panics for null, patterns and empty lists, see `try_sig`.
+    #[deprecated(
+        since = "0.2.0",
+        note = "panics for null, patterns and empty lists, use `try_sig` instead"
+    )]
     pub fn sig(&self) -> Signature {
-        match self {
+        self.try_sig().expect("field has no type signature")
+    }
+
+    /// Converts type value to type signature, `None` for null, patterns and empty lists.
+    pub fn try_sig(&self) -> Option<Signature> {
+        Some(match self {
             Self::IStr(_) => Signature::IStr,
             Self::UStr { .. } => Signature::UStr,
             Self::Char(_) => Signature::Char,
@@ -243,7 +474,15 @@ impl Field {
             Self::U32(_) => Signature::U64,
             Self::U64(_) => Signature::U64,
             Self::Bool(_) => Signature::Bool,
-        }
+            Self::Enum(def, _) => Signature::Enum(def.clone()),
+            Self::List(items) => Signature::List(Box::new(items.first()?.try_sig()?)),
+            Self::Ip(_) => Signature::Ip,
+            Self::Cidr(..) => Signature::Cidr,
+            Self::Date(_) => Signature::Date,
+            Self::DateTime { .. } => Signature::DateTime,
+            Self::Duration(_) => Signature::Duration,
+            Self::Pattern(_) | Self::Null => return None,
+        })
     }
 }
 
@@ -263,11 +502,11 @@ impl PartialEq for Field {
                 _ => false,
             },
             Self::F32(a) => match other {
-                Self::F32(b) => a == b,
+                Self::F32(b) => a.total_cmp(b).is_eq(),
                 _ => false,
             },
             Self::F64(a) => match other {
-                Self::F64(b) => a == b,
+                Self::F64(b) => a.total_cmp(b).is_eq(),
                 _ => false,
             },
             Self::I8(a) => match other {
@@ -306,10 +545,127 @@ impl PartialEq for Field {
                 Self::Bool(b) => a == b,
                 _ => false,
             },
+            Self::Enum(a, i) => match other {
+                Self::Enum(b, j) => a.name() == b.name() && i == j,
+                _ => false,
+            },
+            Self::List(a) => match other {
+                Self::List(b) => a == b,
+                _ => false,
+            },
+            Self::Ip(a) => match other {
+                Self::Ip(b) => a == b,
+                _ => false,
+            },
+            Self::Cidr(a, m) => match other {
+                Self::Cidr(b, n) => a == b && m == n,
+                _ => false,
+            },
+            Self::Date(a) => match other {
+                Self::Date(b) => a == b,
+                _ => false,
+            },
+            Self::DateTime {
+                secs: a, nanos: m, ..
+            } => match other {
+                Self::DateTime {
+                    secs: b, nanos: n, ..
+                } => (a, m) == (b, n),
+                _ => false,
+            },
+            Self::Duration(a) => match other {
+                Self::Duration(b) => a == b,
+                _ => false,
+            },
+            Self::Pattern(a) => match other {
+                Self::Pattern(b) => a == b,
+                _ => false,
+            },
+            Self::Null => other.is_null(),
         }
     }
 }
 impl Eq for Field {}
+impl PartialOrd for Field {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+/// Fields of the same type are compared by value, ustr is compared case insensitively.
+///
+/// Enum values are ordered by declaration order, lists are compared element by element.
+///
+/// Datetimes are compared by instant, regardless of the offset they were written in.
+///
+/// Fields of different types are ordered by type, null comes before any value and patterns after.
+impl Ord for Field {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::IStr(a), Self::IStr(b)) => a.cmp(b),
+            (Self::UStr { lower: a, .. }, Self::UStr { lower: b, .. }) => a.cmp(b),
+            (Self::Char(a), Self::Char(b)) => a.cmp(b),
+            (Self::F32(a), Self::F32(b)) => a.total_cmp(b),
+            (Self::F64(a), Self::F64(b)) => a.total_cmp(b),
+            (Self::I8(a), Self::I8(b)) => a.cmp(b),
+            (Self::I16(a), Self::I16(b)) => a.cmp(b),
+            (Self::I32(a), Self::I32(b)) => a.cmp(b),
+            (Self::I64(a), Self::I64(b)) => a.cmp(b),
+            (Self::U8(a), Self::U8(b)) => a.cmp(b),
+            (Self::U16(a), Self::U16(b)) => a.cmp(b),
+            (Self::U32(a), Self::U32(b)) => a.cmp(b),
+            (Self::U64(a), Self::U64(b)) => a.cmp(b),
+            (Self::Bool(a), Self::Bool(b)) => a.cmp(b),
+            (Self::Enum(a, i), Self::Enum(b, j)) => a.name().cmp(b.name()).then(i.cmp(j)),
+            (Self::List(a), Self::List(b)) => a.cmp(b),
+            (Self::Ip(a), Self::Ip(b)) => a.cmp(b),
+            (Self::Cidr(a, m), Self::Cidr(b, n)) => (a, m).cmp(&(b, n)),
+            (Self::Date(a), Self::Date(b)) => a.cmp(b),
+            (
+                Self::DateTime {
+                    secs: a, nanos: m, ..
+                },
+                Self::DateTime {
+                    secs: b, nanos: n, ..
+                },
+            ) => (a, m).cmp(&(b, n)),
+            (Self::Duration(a), Self::Duration(b)) => a.cmp(b),
+            (Self::Pattern(a), Self::Pattern(b)) => a.cmp(b),
+            (Self::Null, _) | (_, Self::Null) => other.is_null().cmp(&self.is_null()),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl Field {
+    /// Position of type in ordering between fields of different types.
+    fn rank(&self) -> u8 {
+        match self {
+            Self::IStr(_) => 0,
+            Self::UStr { .. } => 1,
+            Self::Char(_) => 2,
+            Self::F32(_) => 3,
+            Self::F64(_) => 4,
+            Self::I8(_) => 5,
+            Self::I16(_) => 6,
+            Self::I32(_) => 7,
+            Self::I64(_) => 8,
+            Self::U8(_) => 9,
+            Self::U16(_) => 10,
+            Self::U32(_) => 11,
+            Self::U64(_) => 12,
+            Self::Bool(_) => 13,
+            Self::Enum(..) => 14,
+            Self::List(_) => 15,
+            Self::Ip(_) => 16,
+            Self::Cidr(..) => 17,
+            Self::Date(_) => 18,
+            Self::DateTime { .. } => 19,
+            Self::Duration(_) => 20,
+            Self::Pattern(_) => 21,
+            Self::Null => 0,
+        }
+    }
+}
 impl Hash for Field {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         match self {
@@ -327,6 +683,51 @@ impl Hash for Field {
             Self::U32(u) => u.hash(state),
             Self::U64(u) => u.hash(state),
             Self::Bool(b) => b.hash(state),
+            Self::Enum(_, i) => i.hash(state),
+            Self::List(items) => items.hash(state),
+            Self::Ip(addr) => addr.hash(state),
+            Self::Cidr(addr, len) => (addr, len).hash(state),
+            Self::Date(days) => days.hash(state),
+            Self::DateTime { secs, nanos, .. } => (secs, nanos).hash(state),
+            Self::Duration(secs) => secs.hash(state),
+            Self::Pattern(pattern) => pattern.hash(state),
+            Self::Null => {}
         }
     }
 }
+
+impl From<Pattern> for Field {
+    fn from(value: Pattern) -> Self {
+        Self::Pattern(Box::new(value))
+    }
+}
+
+impl From<&str> for Field {
+    fn from(value: &str) -> Self {
+        Self::IStr(value.to_string())
+    }
+}
+
+impl From<String> for Field {
+    fn from(value: String) -> Self {
+        Self::IStr(value)
+    }
+}
+
+macro_rules! from_primitive {
+    ($($t:ty => $variant:ident),*) => {
+        $(
+            impl From<$t> for Field {
+                fn from(value: $t) -> Self {
+                    Self::$variant(value)
+                }
+            }
+        )*
+    };
+}
+
+from_primitive!(
+    char => Char, bool => Bool, IpAddr => Ip, f32 => F32, f64 => F64,
+    i8 => I8, i16 => I16, i32 => I32, i64 => I64,
+    u8 => U8, u16 => U16, u32 => U32, u64 => U64
+);
@@ -1,18 +1,36 @@
-use std::fmt::Display;
+use std::{fmt::Display, net::IpAddr, ops::Bound};
 
-use crate::{Field, SchemaOne, Signature};
+use crate::{trie::network, Error, Field, ParseError, SchemaOne, Signature, NULL};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 /// Represents a search query.
 pub struct Filter {
     record: String,
-    args: Vec<Option<Field>>,
+    cond: Condition,
+    sort: Vec<(usize, bool)>,
+    offset: usize,
+    limit: Option<usize>,
 }
 
 impl Filter {
-    /// Return arguments represented
-    pub fn args(&self) -> &[Option<Field>] {
-        &self.args
+    /// Return condition represented
+    pub fn condition(&self) -> &Condition {
+        &self.cond
+    }
+
+    /// Return columns to order results by, `true` for descending.
+    pub fn sort(&self) -> &[(usize, bool)] {
+        &self.sort
+    }
+
+    /// Return number of results skipped.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Return maximum number of results.
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
     }
 
     /// Return record type represented
@@ -21,51 +39,759 @@ impl Filter {
     }
 
     /// Constructs new self
+    /// - `None` represents a wildcard.
+    /// - `Some` matches fields equal to the value.
     pub fn new(record: String, args: &[Option<Field>]) -> Self {
-        Self {
+        Self::with_predicates(
             record,
-            args: args
-                .iter()
-                .map(|arg| arg.as_ref().map(|a| a.upcast()))
+            args.iter()
+                .map(|arg| match arg {
+                    Some(field) => Predicate::Eq(field.clone()),
+                    None => Predicate::Any,
+                })
                 .collect(),
+        )
+    }
+
+    /// Constructs new self with a predicate for each field.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// // whitelist * * >=123457
+    /// let filter = Filter::with_predicates(
+    ///     "whitelist".to_string(),
+    ///     vec![Predicate::Any, Predicate::Any, Predicate::Ge(Field::U64(123457))],
+    /// );
+    /// ```
+    pub fn with_predicates(record: String, args: Vec<Predicate>) -> Self {
+        Self::with_condition(record, Condition::Fields(args))
+    }
+
+    /// Constructs new self from a condition over multiple fields.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// // user is joe or bob, and is not expired
+    /// let filter = Filter::with_condition(
+    ///     "whitelist".to_string(),
+    ///     Condition::field(0, Predicate::Eq(Field::IStr("joe".to_string())))
+    ///         .or(Condition::field(0, Predicate::Eq(Field::IStr("bob".to_string()))))
+    ///         .and(!Condition::field(2, Predicate::Lt(Field::U64(123457)))),
+    /// );
+    /// ```
+    pub fn with_condition(record: String, cond: Condition) -> Self {
+        Self {
+            record,
+            cond: cond.map(&Field::upcast),
+            sort: Vec::new(),
+            offset: 0,
+            limit: None,
+        }
+    }
+
+    /// Constructs new self from text arguments, parsed to the types of the definition.
+    /// - `*` represents a wildcard.
+    /// - `null` matches null in nullable columns.
+    /// - Addresses match networks containing them in cidr columns.
+    /// - Other arguments match fields equal to the parsed value.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// # let doc = Options::default().with("whitelist: ustr istr u64").open().unwrap();
+    /// let schema = doc.schema("whitelist").unwrap();
+    /// let filter = Filter::parse(schema, &["*", "127.0.0.1", "123456"]).unwrap();
+    /// ```
+    pub fn parse(schema: &SchemaOne, args: &[&str]) -> Result<Self, Error> {
+        if args.len() != schema.sig().len() {
+            return Err(Error::ParseError {
+                location: "filter".to_string(),
+                line: 0,
+                reason: ParseError::ArgumentLengthMismatch {
+                    expected: schema.sig().len(),
+                    got: args.len(),
+                },
+            });
+        }
+
+        let mut preds = Vec::with_capacity(args.len());
+
+        for ((arg, sig), column) in args.iter().zip(schema.sig()).zip(schema.columns()) {
+            preds.push(match *arg {
+                "*" => Predicate::Any,
+                "null" if column.nullable() => Predicate::IsNull,
+                _ => Predicate::parse(sig, arg, "filter")?,
+            });
+        }
+
+        Ok(Self::with_predicates(schema.label().to_string(), preds))
+    }
+
+    /// Constructs new self from predicates on named columns, other columns match any value.
+    /// - Predicates on the same column must all match.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// # let doc = Options::default().with("whitelist: name:ustr ip:istr expiry:u64").open().unwrap();
+    /// let schema = doc.schema("whitelist").unwrap();
+    /// let filter = Filter::named(schema, &[("ip", Predicate::Eq(Field::from("127.0.0.1")))]).unwrap();
+    /// ```
+    pub fn named(schema: &SchemaOne, args: &[(&str, Predicate)]) -> Result<Self, Error> {
+        let mut preds = vec![Predicate::Any; schema.sig().len()];
+
+        for (name, pred) in args {
+            let column = schema.position(name).ok_or(Error::UnknownColumn {
+                label: schema.label().to_string(),
+                column: name.to_string(),
+            })?;
+            preds[column] = match &preds[column] {
+                Predicate::Any => pred.clone(),
+                other => other.clone().and(pred.clone()),
+            };
         }
+
+        Ok(Self::with_predicates(schema.label().to_string(), preds))
+    }
+
+    /// Combine with another condition, matches if both match.
+    pub fn and(mut self, cond: Condition) -> Self {
+        self.cond = self.cond.and(cond.map(&Field::upcast));
+        self
+    }
+
+    /// Combine with another condition, matches if either matches.
+    pub fn or(mut self, cond: Condition) -> Self {
+        self.cond = self.cond.or(cond.map(&Field::upcast));
+        self
+    }
+
+    /// Order results by a column, after columns already sorted by.
+    /// - `ustr` columns are ordered case insensitively.
+    /// - Results equal in all sorted columns stay in the order they are declared.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// // second page of 20 entries, latest expiry first
+    /// let filter = filter!("whitelist"; *, *, *)
+    ///     .with_sort(2, true)
+    ///     .with_offset(20)
+    ///     .with_limit(20);
+    /// ```
+    pub fn with_sort(mut self, column: usize, desc: bool) -> Self {
+        self.sort.push((column, desc));
+        self
+    }
+
+    /// Skip a number of results.
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
     }
 
-    /// Apply a type definition to convert all istr to ustr where appropriate.
+    /// Return at most a number of results.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Apply a type definition to convert all fields to the type of their column where possible.
     pub fn apply(&mut self, schema: &SchemaOne) {
-        for (arg, sig) in self.args.iter_mut().zip(schema.sig()) {
-            if let Some(arg) = arg {
-                if sig == &Signature::UStr {
-                    match arg {
-                        Field::IStr(s) => {
-                            *arg = Field::UStr {
-                                original: s.clone(),
-                                lower: s.to_lowercase(),
-                            }
-                        }
-                        _ => unreachable!("wrong type"),
-                    }
+        self.cond.apply(schema.sig());
+    }
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{} {}", self.record, self.cond))?;
+
+        if !self.sort.is_empty() {
+            f.write_str(" | sort")?;
+            for (column, desc) in self.sort.iter() {
+                f.write_fmt(format_args!(
+                    " {column}{}",
+                    if *desc { " desc" } else { "" }
+                ))?;
+            }
+        }
+        if self.offset != 0 {
+            f.write_fmt(format_args!(" | offset {}", self.offset))?;
+        }
+        if let Some(limit) = self.limit {
+            f.write_fmt(format_args!(" | limit {limit}"))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// Represents a condition on the fields of a record.
+pub enum Condition {
+    /// A predicate for each field, must match record length.
+    Fields(Vec<Predicate>),
+    /// A predicate on the field at index, all other fields match any value.
+    Field(usize, Predicate),
+    /// Matches if all conditions match.
+    And(Vec<Condition>),
+    /// Matches if any condition matches.
+    Or(Vec<Condition>),
+    /// Matches if condition does not match.
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    /// Constructs a predicate on the field at index.
+    pub fn field(index: usize, pred: Predicate) -> Self {
+        Self::Field(index, pred)
+    }
+
+    /// Combine with another condition, matches if both match.
+    pub fn and(self, other: Self) -> Self {
+        match self {
+            Self::And(mut conds) => {
+                conds.push(other);
+                Self::And(conds)
+            }
+            _ => Self::And(vec![self, other]),
+        }
+    }
+
+    /// Combine with another condition, matches if either matches.
+    pub fn or(self, other: Self) -> Self {
+        match self {
+            Self::Or(mut conds) => {
+                conds.push(other);
+                Self::Or(conds)
+            }
+            _ => Self::Or(vec![self, other]),
+        }
+    }
+
+    /// Check if record fields satisfy the condition.
+    pub fn matches(&self, fields: &[Field]) -> bool {
+        match self {
+            Self::Fields(preds) => preds
+                .iter()
+                .zip(fields.iter())
+                .all(|(pred, field)| pred.matches(&field.upcast())),
+            Self::Field(index, pred) => fields
+                .get(*index)
+                .is_some_and(|field| pred.matches(&field.upcast())),
+            Self::And(conds) => conds.iter().all(|cond| cond.matches(fields)),
+            Self::Or(conds) => conds.iter().any(|cond| cond.matches(fields)),
+            Self::Not(cond) => !cond.matches(fields),
+        }
+    }
+
+    /// Returns a predicate for each of `len` fields, such that every record matching the condition matches all predicates.
+    /// - The predicates are used to search the index.
+    /// - Records found still need to be checked against the condition, unless it is `exact`.
+    pub fn narrow(&self, len: usize) -> Vec<Predicate> {
+        match self {
+            Self::Fields(preds) => preds.clone(),
+            Self::Field(index, pred) => {
+                let mut preds = vec![Predicate::Any; len];
+                if let Some(p) = preds.get_mut(*index) {
+                    *p = pred.clone();
                 }
+                preds
+            }
+            Self::And(conds) => conds.iter().fold(vec![Predicate::Any; len], |acc, cond| {
+                acc.into_iter()
+                    .zip(cond.narrow(len))
+                    .map(|(a, b)| a.and(b))
+                    .collect()
+            }),
+            Self::Or(conds) => {
+                let mut narrowed = conds.iter().map(|cond| cond.narrow(len));
+                let first = narrowed
+                    .next()
+                    .unwrap_or_else(|| vec![Predicate::None; len]);
+                narrowed.fold(first, |acc, preds| {
+                    acc.into_iter().zip(preds).map(|(a, b)| a.or(b)).collect()
+                })
             }
+            Self::Not(_) => vec![Predicate::Any; len],
+        }
+    }
+
+    /// Returns true if records matching predicates from `narrow` always match the condition.
+    pub fn exact(&self) -> bool {
+        match self {
+            Self::Fields(_) | Self::Field(..) => true,
+            Self::And(conds) => conds.iter().all(Self::exact),
+            Self::Or(_) | Self::Not(_) => false,
+        }
+    }
+
+    /// Check if condition can be applied to records of the type signature.
+    pub fn fits(&self, sig: &[Signature]) -> bool {
+        let fits = |pred: &Predicate, sig: &Signature| pred.cast(sig).is_some();
+
+        match self {
+            Self::Fields(preds) => {
+                preds.len() == sig.len() && preds.iter().zip(sig.iter()).all(|(p, s)| fits(p, s))
+            }
+            Self::Field(index, pred) => sig.get(*index).is_some_and(|s| fits(pred, s)),
+            Self::And(conds) | Self::Or(conds) => conds.iter().all(|cond| cond.fits(sig)),
+            Self::Not(cond) => cond.fits(sig),
+        }
+    }
+
+    /// Convert all fields to the type of their column where possible.
+    pub fn apply(&mut self, sig: &[Signature]) {
+        let apply = |pred: &mut Predicate, sig: &Signature| {
+            if let Some(cast) = pred.cast(sig) {
+                *pred = cast;
+            }
+        };
+
+        match self {
+            Self::Fields(preds) => preds
+                .iter_mut()
+                .zip(sig.iter())
+                .for_each(|(p, s)| apply(p, s)),
+            Self::Field(index, pred) => {
+                if let Some(s) = sig.get(*index) {
+                    apply(pred, s)
+                }
+            }
+            Self::And(conds) | Self::Or(conds) => conds.iter_mut().for_each(|cond| cond.apply(sig)),
+            Self::Not(cond) => cond.apply(sig),
+        }
+    }
+
+    /// Converts all values the condition compares against.
+    pub fn map(&self, f: &impl Fn(&Field) -> Field) -> Self {
+        match self {
+            Self::Fields(preds) => Self::Fields(preds.iter().map(|p| p.map(f)).collect()),
+            Self::Field(index, pred) => Self::Field(*index, pred.map(f)),
+            Self::And(conds) => Self::And(conds.iter().map(|c| c.map(f)).collect()),
+            Self::Or(conds) => Self::Or(conds.iter().map(|c| c.map(f)).collect()),
+            Self::Not(cond) => Self::Not(Box::new(cond.map(f))),
         }
     }
 }
 
-impl Display for Filter {
+impl std::ops::Not for Condition {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        match self {
+            Self::Not(cond) => *cond,
+            _ => Self::Not(Box::new(self)),
+        }
+    }
+}
+
+impl Display for Condition {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!(
-            "{} {}",
-            self.record,
-            &self
-                .args
-                .iter()
-                .map(|arg| {
-                    arg.as_ref()
-                        .map(|a| a.to_string())
-                        .unwrap_or("*".to_string())
+        match self {
+            Self::Fields(preds) => f.write_str(
+                &preds
+                    .iter()
+                    .map(Predicate::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            Self::Field(index, pred) => match pred {
+                Predicate::Contains(_) => f.write_fmt(format_args!("{index} {pred}")),
+                Predicate::Ne(_)
+                | Predicate::NotNull
+                | Predicate::Gt(_)
+                | Predicate::Ge(_)
+                | Predicate::Lt(_)
+                | Predicate::Le(_) => f.write_fmt(format_args!("{index}{pred}")),
+                _ => f.write_fmt(format_args!("{index}={pred}")),
+            },
+            Self::And(conds) => f.write_fmt(format_args!(
+                "({})",
+                conds
+                    .iter()
+                    .map(Condition::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            )),
+            Self::Or(conds) => f.write_fmt(format_args!(
+                "({})",
+                conds
+                    .iter()
+                    .map(Condition::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            )),
+            Self::Not(cond) => f.write_fmt(format_args!("not {cond}")),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// Represents a condition on a single field.
+pub enum Predicate {
+    /// Matches any value.
+    Any,
+    /// Equal to value.
+    Eq(Field),
+    /// Not equal to value.
+    Ne(Field),
+    /// Greater than value.
+    Gt(Field),
+    /// Greater than or equal to value.
+    Ge(Field),
+    /// Less than value.
+    Lt(Field),
+    /// Less than or equal to value.
+    Le(Field),
+    /// Between two values, inclusive.
+    Between(Field, Field),
+    /// List containing value, or network containing address or network, see `Field::List` and `Field::Cidr`.
+    Contains(Field),
+    /// Matches null, see `Field::Null`.
+    IsNull,
+    /// Matches any value except null.
+    NotNull,
+    /// Matches no value.
+    None,
+    /// Matches if all predicates match.
+    And(Vec<Predicate>),
+    /// Matches if any predicate matches.
+    Or(Vec<Predicate>),
+    /// Matches if predicate does not match.
+    Not(Box<Predicate>),
+}
+
+impl Predicate {
+    /// Check if field satisfies the condition.
+    /// - Null is only greater or less than a value if the value is null.
+    /// - Patterns are never greater or less than a value, see `Field::Pattern`.
+    pub fn matches(&self, field: &Field) -> bool {
+        let ordered = field.as_pattern().is_none();
+        match self {
+            Self::Any => true,
+            Self::Eq(value) => field == value,
+            Self::Ne(value) => field != value,
+            Self::Gt(value) => ordered && field > value,
+            Self::Ge(value) => ordered && field >= value,
+            Self::Lt(value) => ordered && !field.is_null() && field < value,
+            Self::Le(value) => ordered && !field.is_null() && field <= value,
+            Self::Between(from, to) => ordered && !field.is_null() && from <= field && field <= to,
+            Self::Contains(value) => match (field, value) {
+                (Field::List(items), _) => items.contains(value),
+                (Field::Cidr(net, len), Field::Cidr(addr, n)) => {
+                    len <= n && network(*addr, *len) == *net
+                }
+                _ => false,
+            },
+            Self::IsNull => field.is_null(),
+            Self::NotNull => !field.is_null(),
+            Self::None => false,
+            Self::And(preds) => preds.iter().all(|pred| pred.matches(field)),
+            Self::Or(preds) => preds.iter().any(|pred| pred.matches(field)),
+            Self::Not(pred) => !pred.matches(field),
+        }
+    }
+
+    /// Combine with another predicate, matches if both match.
+    pub fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Any, pred) | (pred, Self::Any) => pred,
+            (Self::And(mut preds), other) => {
+                preds.push(other);
+                Self::And(preds)
+            }
+            (pred, other) => Self::And(vec![pred, other]),
+        }
+    }
+
+    /// Combine with another predicate, matches if either matches.
+    pub fn or(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Any, _) | (_, Self::Any) => Self::Any,
+            (Self::None, pred) | (pred, Self::None) => pred,
+            (Self::Or(mut preds), other) => {
+                preds.push(other);
+                Self::Or(preds)
+            }
+            (pred, other) => Self::Or(vec![pred, other]),
+        }
+    }
+
+    /// Returns range of values that can satisfy the condition, `None` if no value can.
+    pub fn bounds(&self) -> Option<(Bound<&Field>, Bound<&Field>)> {
+        let bounds = match self {
+            Self::Any | Self::Ne(_) | Self::Contains(_) | Self::Not(_) => {
+                (Bound::Unbounded, Bound::Unbounded)
+            }
+            Self::Eq(value) => (Bound::Included(value), Bound::Included(value)),
+            Self::Gt(value) => (Bound::Excluded(value), Bound::Unbounded),
+            Self::Ge(value) => (Bound::Included(value), Bound::Unbounded),
+            Self::Lt(value) => (Bound::Unbounded, Bound::Excluded(value)),
+            Self::Le(value) => (Bound::Unbounded, Bound::Included(value)),
+            Self::Between(from, to) => (Bound::Included(from), Bound::Included(to)),
+            Self::IsNull => (Bound::Included(&NULL), Bound::Included(&NULL)),
+            Self::NotNull => (Bound::Excluded(&NULL), Bound::Unbounded),
+            Self::None => return None,
+            Self::And(preds) => {
+                let mut bounds = (Bound::Unbounded, Bound::Unbounded);
+                for pred in preds {
+                    let (start, end) = pred.bounds()?;
+                    bounds = (
+                        Self::start(bounds.0, start, true),
+                        Self::end(bounds.1, end, true),
+                    );
+                }
+                bounds
+            }
+            Self::Or(preds) => {
+                let mut all = preds.iter().filter_map(Self::bounds);
+                let first = all.next()?;
+                all.fold(first, |bounds, (start, end)| {
+                    (
+                        Self::start(bounds.0, start, false),
+                        Self::end(bounds.1, end, false),
+                    )
                 })
-                .collect::<Vec<_>>()
-                .join(" "),
-        ))
+            }
+        };
+
+        let empty = match bounds {
+            (Bound::Included(start), Bound::Included(end)) => start > end,
+            (
+                Bound::Included(start) | Bound::Excluded(start),
+                Bound::Included(end) | Bound::Excluded(end),
+            ) => start >= end,
+            _ => false,
+        };
+
+        (!empty).then_some(bounds)
+    }
+
+    /// Pick the tighter (or looser) of two start bounds.
+    fn start<'a>(a: Bound<&'a Field>, b: Bound<&'a Field>, tighter: bool) -> Bound<&'a Field> {
+        let a_first = match (a, b) {
+            (Bound::Unbounded, _) => true,
+            (_, Bound::Unbounded) => false,
+            (Bound::Included(x) | Bound::Excluded(x), Bound::Included(y) | Bound::Excluded(y))
+                if x != y =>
+            {
+                x < y
+            }
+            _ => matches!(a, Bound::Included(_)),
+        };
+
+        if a_first != tighter {
+            a
+        } else {
+            b
+        }
+    }
+
+    /// Pick the tighter (or looser) of two end bounds.
+    fn end<'a>(a: Bound<&'a Field>, b: Bound<&'a Field>, tighter: bool) -> Bound<&'a Field> {
+        let a_last = match (a, b) {
+            (Bound::Unbounded, _) => true,
+            (_, Bound::Unbounded) => false,
+            (Bound::Included(x) | Bound::Excluded(x), Bound::Included(y) | Bound::Excluded(y))
+                if x != y =>
+            {
+                x > y
+            }
+            _ => matches!(a, Bound::Included(_)),
+        };
+
+        if a_last != tighter {
+            a
+        } else {
+            b
+        }
+    }
+
+    /// Returns values the condition compares against.
+    pub fn fields(&self) -> Vec<&Field> {
+        match self {
+            Self::Any | Self::None | Self::IsNull | Self::NotNull => Vec::new(),
+            Self::Eq(value)
+            | Self::Ne(value)
+            | Self::Gt(value)
+            | Self::Ge(value)
+            | Self::Lt(value)
+            | Self::Le(value)
+            | Self::Contains(value) => vec![value],
+            Self::Between(from, to) => vec![from, to],
+            Self::And(preds) | Self::Or(preds) => preds.iter().flat_map(Self::fields).collect(),
+            Self::Not(pred) => pred.fields(),
+        }
     }
+
+    /// Converts all values the condition compares against.
+    pub fn map(&self, f: &impl Fn(&Field) -> Field) -> Self {
+        match self {
+            Self::Any => Self::Any,
+            Self::None => Self::None,
+            Self::IsNull => Self::IsNull,
+            Self::NotNull => Self::NotNull,
+            Self::Eq(value) => Self::Eq(f(value)),
+            Self::Ne(value) => Self::Ne(f(value)),
+            Self::Gt(value) => Self::Gt(f(value)),
+            Self::Ge(value) => Self::Ge(f(value)),
+            Self::Lt(value) => Self::Lt(f(value)),
+            Self::Le(value) => Self::Le(f(value)),
+            Self::Between(from, to) => Self::Between(f(from), f(to)),
+            Self::Contains(value) => Self::Contains(f(value)),
+            Self::And(preds) => Self::And(preds.iter().map(|pred| pred.map(f)).collect()),
+            Self::Or(preds) => Self::Or(preds.iter().map(|pred| pred.map(f)).collect()),
+            Self::Not(pred) => Self::Not(Box::new(pred.map(f))),
+        }
+    }
+
+    /// Converts all values the condition compares against to the type and upcast them, `None` if any cannot be converted.
+    /// - Values a list contains are converted to the type of its elements.
+    /// - Addresses compared equal to networks match networks containing them.
+    pub(crate) fn cast(&self, sig: &Signature) -> Option<Self> {
+        Some(match self {
+            Self::Contains(value) if *sig == Signature::Cidr => Self::Contains(sig.cast(value)?),
+            Self::Contains(value) => Self::Contains(sig.element()?.cast(value)?.upcast()),
+            Self::Eq(value) if *sig == Signature::Cidr && Self::address(value) => {
+                Self::Contains(sig.cast(value)?)
+            }
+            Self::And(preds) => Self::And(
+                preds
+                    .iter()
+                    .map(|pred| pred.cast(sig))
+                    .collect::<Option<_>>()?,
+            ),
+            Self::Or(preds) => Self::Or(
+                preds
+                    .iter()
+                    .map(|pred| pred.cast(sig))
+                    .collect::<Option<_>>()?,
+            ),
+            Self::Not(pred) => Self::Not(Box::new(pred.cast(sig)?)),
+            _ if self.fields().iter().all(|field| sig.cast(field).is_some()) => {
+                self.map(&|field| sig.cast(field).unwrap().upcast())
+            }
+            _ => return None,
+        })
+    }
+}
+
+impl Predicate {
+    /// Parse a value matching fields equal to it, or networks containing it if it is an address in a cidr column.
+    pub(crate) fn parse(sig: &Signature, s: &str, location: &str) -> Result<Self, Error> {
+        let value = sig.parse(s, location, 0)?;
+        Ok(match value {
+            Field::Cidr(..) if !s.contains('/') => Self::Contains(value),
+            _ => Self::Eq(value),
+        })
+    }
+
+    /// Returns true for an address, or a string written as one.
+    fn address(value: &Field) -> bool {
+        matches!(value, Field::Ip(_)) || value.as_str().is_some_and(|s| s.parse::<IpAddr>().is_ok())
+    }
+}
+
+impl std::ops::Not for Predicate {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        match self {
+            Self::Not(pred) => *pred,
+            _ => Self::Not(Box::new(self)),
+        }
+    }
+}
+
+impl Display for Predicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Any => f.write_str("*"),
+            Self::Eq(value) => f.write_fmt(format_args!("{value}")),
+            Self::Ne(value) => f.write_fmt(format_args!("!={value}")),
+            Self::Gt(value) => f.write_fmt(format_args!(">{value}")),
+            Self::Ge(value) => f.write_fmt(format_args!(">={value}")),
+            Self::Lt(value) => f.write_fmt(format_args!("<{value}")),
+            Self::Le(value) => f.write_fmt(format_args!("<={value}")),
+            Self::Between(from, to) => f.write_fmt(format_args!("{from}..={to}")),
+            Self::Contains(value) => f.write_fmt(format_args!("has {value}")),
+            Self::IsNull => f.write_str("null"),
+            Self::NotNull => f.write_str("!=null"),
+            Self::None => f.write_str("!*"),
+            Self::And(preds) => f.write_fmt(format_args!(
+                "({})",
+                preds
+                    .iter()
+                    .map(Predicate::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            )),
+            Self::Or(preds) => f.write_fmt(format_args!(
+                "({})",
+                preds
+                    .iter()
+                    .map(Predicate::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            )),
+            Self::Not(pred) => f.write_fmt(format_args!("not {pred}")),
+        }
+    }
+}
+
+/// Constructs a `Filter` with a predicate for each field.
+/// - `*` matches any value.
+/// - `null` and `!= null` match null and any value except null.
+/// - `value` matches fields equal to the value.
+/// - `!= value`, `> value`, `>= value`, `< value` and `<= value` compare against the value.
+/// - `has value` matches lists containing the value.
+///
+/// Values are converted to the type of their column when the filter is applied,
+/// so `"127.0.0.1"` works for both `istr` and `ustr` columns, and `5` for any integer column.
+///
+/// ```
+/// # use simplerecords::*;
+/// # let doc = Document::load_as(
+/// #     "whitelist: ustr istr u64\nwhitelist joe 127.0.0.1 123456",
+/// #     std::path::Path::new("whitelist"),
+/// # ).unwrap();
+/// let found = doc.find(filter!("whitelist"; *, "127.0.0.1", >= 123456)).unwrap();
+/// # assert_eq!(found.len(), 1);
+/// ```
+#[macro_export]
+macro_rules! filter {
+    (@args [$($out:expr,)*]) => {
+        vec![$($out),*]
+    };
+    (@args [$($out:expr,)*] * $(, $($rest:tt)*)?) => {
+        $crate::filter!(@args [$($out,)* $crate::Predicate::Any,] $($($rest)*)?)
+    };
+    (@args [$($out:expr,)*] null $(, $($rest:tt)*)?) => {
+        $crate::filter!(@args [$($out,)* $crate::Predicate::IsNull,] $($($rest)*)?)
+    };
+    (@args [$($out:expr,)*] != null $(, $($rest:tt)*)?) => {
+        $crate::filter!(@args [$($out,)* $crate::Predicate::NotNull,] $($($rest)*)?)
+    };
+    (@args [$($out:expr,)*] != $value:expr $(, $($rest:tt)*)?) => {
+        $crate::filter!(@args [$($out,)* $crate::Predicate::Ne($crate::Field::from($value)),] $($($rest)*)?)
+    };
+    (@args [$($out:expr,)*] >= $value:expr $(, $($rest:tt)*)?) => {
+        $crate::filter!(@args [$($out,)* $crate::Predicate::Ge($crate::Field::from($value)),] $($($rest)*)?)
+    };
+    (@args [$($out:expr,)*] > $value:expr $(, $($rest:tt)*)?) => {
+        $crate::filter!(@args [$($out,)* $crate::Predicate::Gt($crate::Field::from($value)),] $($($rest)*)?)
+    };
+    (@args [$($out:expr,)*] <= $value:expr $(, $($rest:tt)*)?) => {
+        $crate::filter!(@args [$($out,)* $crate::Predicate::Le($crate::Field::from($value)),] $($($rest)*)?)
+    };
+    (@args [$($out:expr,)*] < $value:expr $(, $($rest:tt)*)?) => {
+        $crate::filter!(@args [$($out,)* $crate::Predicate::Lt($crate::Field::from($value)),] $($($rest)*)?)
+    };
+    (@args [$($out:expr,)*] has $value:expr $(, $($rest:tt)*)?) => {
+        $crate::filter!(@args [$($out,)* $crate::Predicate::Contains($crate::Field::from($value)),] $($($rest)*)?)
+    };
+    (@args [$($out:expr,)*] $value:expr $(, $($rest:tt)*)?) => {
+        $crate::filter!(@args [$($out,)* $crate::Predicate::Eq($crate::Field::from($value)),] $($($rest)*)?)
+    };
+    ($record:expr; $($args:tt)*) => {
+        $crate::Filter::with_predicates($record.to_string(), $crate::filter!(@args [] $($args)*))
+    };
 }
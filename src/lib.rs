@@ -10,7 +10,7 @@
 //!
 //! ```
 //! [dependencies]
-//! simplerecords = "0.1"
+//! simplerecords = "0.2"
 //! ```
 //!
 //! Here are some reasons to use *Simple Records*.
@@ -33,6 +33,10 @@
 //! whitelist:      ustr        istr        u64
 //! #                           ^--- signed string
 //! ```
+//! Columns can be **named**, so records and filters can refer to them by name.
+//! ```text
+//! whitelist:      name:ustr   ip:istr     expiry:u64
+//! ```
 //! **Record** has a *type* and fields.
 //! ```
 //! whitelist       joe         127.0.0.1   123456
@@ -58,7 +62,10 @@
 //! ```
 //! 2. Create a search filter for `* '127.0.0.1' *`.
 //! ```
-//! let filter = Filter::new("whitelist", &[None, Some("127.0.0.1"), None])
+//! let filter = doc.parse_filter("whitelist", &["*", "127.0.0.1", "*"]);
+//!
+//! // or with the filter macro
+//! let filter = filter!("whitelist"; *, "127.0.0.1", >= 123456);
 //! ```
 //! 3. Run the search.
 //! ```
@@ -82,6 +89,64 @@
 //!     .open();
 //! ```
 //!
+//! ### Queries
+//!
+//! Queries are written like records, with comparisons by column index and stages after `|`.
+//!
+//! ```
+//! # use simplerecords::*;
+//! # let doc = Options::default().with("whitelist: ustr istr u64").open().unwrap();
+//! let found = doc.query(r#"whitelist 0="joe" 2>1000 | sort 2 desc | limit 10"#);
+//! let found = doc.query("whitelist (0=joe or 0=bob) and not 2<123457");
+//! ```
+//!
+//! ### Aggregates
+//!
+//! Records matching a filter can be counted, summarised and projected to distinct values.
+//!
+//! ```
+//! # use simplerecords::*;
+//! # let doc = Options::default().with("whitelist: ustr istr u64").open().unwrap();
+//! # let filter = filter!("whitelist"; *, *, *);
+//! let joe_ips = doc.count(filter!("whitelist"; "joe", *, *));
+//! let values = doc.aggregate(filter.clone(), &[Aggregate::Count, Aggregate::Max(2)]);
+//! let per_user = doc.group_by(filter.clone(), &[0], &[Aggregate::Count]); // ordered by user
+//! let ips = doc.distinct(filter!("whitelist"; *, *, *), &[1]); // every IP, once
+//! ```
+//!
+//! ### Joins
+//!
+//! Records of two types can be paired on equal columns, the second type is looked up by index where possible.
+//!
+//! ```
+//! # use simplerecords::*;
+//! # let doc = Options::default().with("user: ustr u8").with("perm: ustr istr bool").open().unwrap();
+//! // user: name level, perm: name permission allowed
+//! let pairs = doc.join(filter!("user"; *, >= 3), 0, filter!("perm"; *, *, true), 0);
+//! ```
+//!
+//! ### Indexes
+//!
+//! Records are indexed by their columns in order, columns can have an extra index with `indexed`.
+//!
+//! ```text
+//! whitelist:  ustr    istr indexed    u64     # filters on IP won't walk every user
+//! ```
+//!
+//! ### Writing
+//!
+//! A document can be written back to rules, definitions and records are aligned by type.
+//!
+//! ```
+//! # use simplerecords::*;
+//! # let mut doc = Options::default().open().unwrap();
+//! let content = doc.to_rules_string();
+//! let files = doc.to_rules_files("root"); // one file per location
+//!
+//! // or write changes in place, keeping comments and layout
+//! doc.save().unwrap();
+//! ```
+//!
 //! ### Scoping
 //!
 //! Sections of the file can be labelled for organisation.
@@ -118,40 +183,117 @@
 //! |istr|Case sensitive string.|
 //! |ustr|Case insensitive string.|
 //! |bool|Boolean value.|
+//! |ip|IPv4 or IPv6 address.|
+//! |ipv4|IPv4 address.|
+//! |ipv6|IPv6 address.|
+//! |cidr|Network of addresses, as in `10.0.0.0/16`.|
+//! |date|Calendar date, as in `2024-02-29`.|
+//! |datetime|RFC 3339 instant, as in `2024-02-29T12:00:00Z`.|
+//! |duration|Length of time in `w`, `d`, `h`, `m` and `s`, as in `30d` or `1h30m`.|
 //!
-//! ## Todo
+//! Any type can be made **nullable** with `?`, as in `u64?`, and `null` is written for no value, even in string columns, while `"null"` is the text.
+//!
+//! Columns can have a **default** value, as in `u64=0` or `u64?=null`, so trailing columns can be left out of records.
+//!
+//! Values can be **constrained** after the type, records that break a constraint fail to parse.
+//! ```text
+//! perm:   name:ustr len 1..=32   permission:ustr in {read, write}   level:u8 1..=100   host:istr glob *.example.com
+//! ```
 //!
-//! All current features are **stable** and there will be no breaking changes.
+//! An **enum** declares a type with a fixed list of values, ordered as declared so ranges like `level >= member` work.
+//! ```text
+//! enum level = guest member admin
+//! user:   name:ustr   level:level
+//! user    joe         admin
+//! ```
 //!
-//! New features will be added until this becomes a text-based database. Including
-//! - Insert/delete/amending records.
-//! - Value constraints
-//! - Filter macro for more concise syntax.
+//! A **list** of any type is written in brackets, as in `[u16]`, and values as `[80,443]`, or quoted to contain spaces. Queries match lists containing a value with `has`, as in `ports has 443`.
+//! ```text
+//! ports:  name:istr   ports:[u16]
+//! ports   web         [80,443]
+//! ports   mail        "[25, 465, 587]"
 //! ```
-//! doc.filter(filter![*, "127.0.0.1", *])
+//!
+//! An address in place of a **cidr** value matches networks containing it, so `whitelist * 10.0.3.7 *` finds the record below. IPv6 addresses contain `:` and are quoted in records.
+//! ```text
+//! whitelist:  name:ustr   net:cidr        expiry:u64
+//! whitelist   joe         10.0.0.0/16     123456
+//! whitelist   bob         "2001:db8::/32" 123457
 //! ```
+//!
+//! A **date**, **datetime** or **duration** is ordered, so ranges like `expiry < 2025-01-01` work. Datetimes contain `:` and are quoted in records, and compare by instant whatever their offset.
+//! ```text
+//! whitelist:  name:ustr   expiry:date     session:duration
+//! whitelist   joe         2025-06-30      12h
+//! whitelist   bob         2024-12-31      30d
+//! ```
+//!
+//! Columns can mark when a record is **valid** with `not_before`, `not_after` (inclusive) or `expires` after a date, datetime or integer type, integers being seconds since epoch. `Document::find_at` only finds records valid at an instant, and `Document::expired` lists records past their bounds.
+//! ```text
+//! cert:   name:istr   from:date not_before   to:date not_after
+//! cert    web         2024-01-01             2024-12-31
+//! whitelist:  name:ustr   expiry:u64 expires
+//! ```
+//!
+//! Columns declared with `pattern` hold **rules** matching many values: `*` for any value, globs like `*.example.com` in string columns and ranges like `1000..2000` in other ordered columns. `Document::match_rules` finds records matching concrete values, most specific first.
+//! ```text
+//! acl:    host:istr pattern   port:u16 pattern    action:istr
+//! acl     *.example.com       443                 allow
+//! acl     *                   1000..2000          deny
+//! ```
+//!
+//! ## Migrating from 0.1
+//!
+//! - `Filter::args` is removed, a filter holds a `Condition` now, read it with `Filter::condition`. Values given to `Filter::new` become `Predicate::Eq` in `Condition::Fields`.
+//! - `Error::FilterMismatch` holds the filter as `got: Box<Filter>`.
+//! - `Pass::destruct` returns enum declarations first, as `(enums, schema, records)`.
+//! - `Schema::parse` takes the declared enums as a second argument.
+//! - `Field::sig` is deprecated as it panics for null, patterns and empty lists, use `Field::try_sig`.
+//! - `Field`, `Signature`, `Error` and `ParseError` have new variants, so matches over them need a new arm or a wildcard.
+//!
+//! ## Todo
+//!
+//! All current features are **stable**, breaking changes only come with a new major version, such as `0.2` to `0.3`.
+//!
+//! New features will be added until this becomes a text-based database.
 
+mod aggregate;
+mod constraint;
 mod document;
+mod enumdef;
 mod error;
 mod field;
 mod filter;
 mod options;
 mod pass;
+mod pattern;
+mod query;
 mod record;
 mod recordset;
 mod schema;
 mod signature;
+mod source;
+mod syntax;
+mod time;
+mod trie;
 
+pub use aggregate::*;
+pub use constraint::*;
 pub use document::*;
+pub use enumdef::*;
 pub use error::*;
 pub use field::*;
 pub use filter::*;
 pub use options::*;
 pub use pass::*;
+pub use pattern::*;
+pub use query::*;
 pub use record::*;
 pub use recordset::*;
 pub use schema::*;
 pub use signature::*;
+pub use source::*;
+pub use syntax::*;
 
 #[cfg(test)]
 mod tests;
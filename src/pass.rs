@@ -1,111 +1,170 @@
-use std::{ffi::OsStr, fs, path::Path};
+use std::{ffi::OsStr, fs, ops::Range, path::Path};
 
-use crate::{Error, ParseError};
+use crate::{Error, ParseError, Source};
 
-const QUOTES: &[char] = &['\'', '"', '`'];
+pub(crate) const QUOTES: &[char] = &['\'', '"', '`'];
 
-/// Splits a file input into two streams.
+/// An argument with the byte range it is written in.
+pub type SpannedArg = (Range<usize>, String);
+
+/// An argument with whether it is quoted.
+pub type QuotedArg = (String, bool);
+
+/// Splits a file input into three streams.
+/// - Enum declarations are parsed first, so definitions can use them as types.
 /// - Definitions are parsed by the schema parser.
 /// - Records are parsed by the records parser, after schema parsing has completed.
 pub struct Pass {
+    // location, line, name, values
+    enums: Vec<(String, u32, String, Vec<String>)>,
     // location, line, label, signature
     schema: Vec<(String, u32, String, Vec<String>)>,
     // location, line, name, args
-    records: Vec<(String, u32, String, Vec<String>)>,
+    records: Vec<(String, u32, String, Vec<QuotedArg>)>,
+    // files read, the first one is the file parsed
+    sources: Vec<Source>,
 }
 
 impl Pass {
     /// Returns the internal types.
+    /// - Record arguments lose whether they are quoted, see `destruct_quoted`.
     #[allow(clippy::type_complexity)]
     pub fn destruct(
         self,
     ) -> (
         Vec<(String, u32, String, Vec<String>)>,
         Vec<(String, u32, String, Vec<String>)>,
+        Vec<(String, u32, String, Vec<String>)>,
+    ) {
+        let (enums, schema, records) = self.destruct_quoted();
+        let records = records
+            .into_iter()
+            .map(|(location, line, label, args)| {
+                (
+                    location,
+                    line,
+                    label,
+                    args.into_iter().map(|(arg, _)| arg).collect(),
+                )
+            })
+            .collect();
+        (enums, schema, records)
+    }
+
+    /// Returns the internal types, with whether each record argument is quoted.
+    #[allow(clippy::type_complexity)]
+    pub fn destruct_quoted(
+        self,
+    ) -> (
+        Vec<(String, u32, String, Vec<String>)>,
+        Vec<(String, u32, String, Vec<String>)>,
+        Vec<(String, u32, String, Vec<QuotedArg>)>,
     ) {
-        (self.schema, self.records)
+        (self.enums, self.schema, self.records)
+    }
+
+    /// Returns files read, the first one is the file parsed.
+    pub fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    /// Split a location into file name and scope, as in `file<scope>`.
+    pub fn split_location(location: &str) -> (&str, Option<&str>) {
+        match location.strip_suffix('>').and_then(|s| s.split_once('<')) {
+            Some((file, scope)) => (file, Some(scope)),
+            None => (location, None),
+        }
     }
 
     /// Remove comments from file content.
     pub fn clear_comment(s: &str) -> Result<String, (u32, ParseError)> {
         let mut cleared = String::new();
+        let mut last = 0;
 
-        let mut multi_escaped = false;
-        let mut multi_tick = false;
-        let mut multi_line = false;
-        let mut line_escaped = false;
-        let mut in_string = None;
+        for range in Self::find_comments(s)? {
+            cleared.push_str(&s[last..range.start]);
+
+            let comment = &s[range.clone()];
+            if comment.starts_with("/*") {
+                match comment.matches('\n').count() {
+                    0 => cleared.push(' '),
+                    n => cleared.extend(std::iter::repeat_n('\n', n)),
+                }
+            }
+
+            last = range.end;
+        }
+
+        cleared.push_str(&s[last..]);
+        Ok(cleared)
+    }
+
+    /// Locate comments in file content, returns the byte range of each comment.
+    /// - Single line comments end before the line break.
+    /// - Multi-line comments include the closing `*/`.
+    pub fn find_comments(s: &str) -> Result<Vec<Range<usize>>, (u32, ParseError)> {
+        let mut comments = Vec::new();
 
+        let mut line_start = None;
+        let mut multi_start = None;
+        let mut in_string = None;
         let mut escaped = false;
 
         let mut line_no = 1;
 
-        for c in s.chars() {
-            if multi_escaped {
+        let mut chars = s.char_indices().peekable();
+
+        while let Some((i, c)) = chars.next() {
+            if let Some(start) = multi_start {
                 match c {
-                    '*' => multi_tick = true,
-                    '/' if multi_tick => {
-                        multi_escaped = false;
-                        multi_tick = false;
-                        if multi_line {
-                            multi_line = false;
-                        } else {
-                            cleared.push(' ');
-                        }
-                    }
-                    '\n' => {
-                        cleared.push('\n');
-                        multi_line = true;
-                        line_no += 1
+                    '*' if chars.next_if(|(_, c)| *c == '/').is_some() => {
+                        comments.push(start..i + 2);
+                        multi_start = None;
                     }
+                    '\n' => line_no += 1,
                     _ => {}
                 }
 
                 continue;
-            } else if line_escaped && c != '\n' {
-                continue;
+            } else if let Some(start) = line_start {
+                if c != '\n' {
+                    continue;
+                }
+                comments.push(start..i);
+                line_start = None;
+            } else if c == '\\' {
+                escaped = !escaped;
+            } else if escaped {
+                escaped = false;
+            } else if let Some(quote) = in_string {
+                if c == quote {
+                    in_string = None;
+                }
             } else {
-                if c == '\\' {
-                    escaped = !escaped;
-                } else if escaped {
-                    escaped = false;
-                } else if let Some(quote) = in_string {
-                    if c == quote {
-                        in_string = None;
+                match c {
+                    '#' => {
+                        line_start = Some(i);
+                        continue;
                     }
-                } else {
-                    match c {
-                        '#' => {
-                            line_escaped = true;
-                            continue;
-                        }
-                        '/' => multi_tick = true,
-                        '*' if multi_tick => {
-                            multi_tick = false;
-                            multi_escaped = true;
-                            cleared.pop();
-                            continue;
-                        }
-                        _ if QUOTES.contains(&c) => in_string = Some(c),
-                        _ => {}
+                    '/' if chars.next_if(|(_, c)| *c == '*').is_some() => {
+                        multi_start = Some(i);
+                        continue;
                     }
+                    _ if QUOTES.contains(&c) => in_string = Some(c),
+                    _ => {}
                 }
+            }
 
-                if c == '\n' {
-                    if in_string.is_some() {
-                        return Err((line_no, ParseError::UnclosedString));
-                    }
-                    multi_tick = false;
-                    line_escaped = false;
-
-                    line_no += 1;
+            if c == '\n' {
+                if in_string.is_some() {
+                    return Err((line_no, ParseError::UnclosedString));
                 }
 
-                cleared.push(c);
+                line_no += 1;
             }
         }
 
-        if multi_escaped {
+        if multi_start.is_some() {
             return Err((line_no, ParseError::UnclosedMultiLineComment));
         }
 
@@ -113,7 +172,11 @@ impl Pass {
             return Err((line_no, ParseError::UnclosedString));
         }
 
-        Ok(cleared)
+        if let Some(start) = line_start {
+            comments.push(start..s.len());
+        }
+
+        Ok(comments)
     }
 
     /// Split a line containing type definition by colon.
@@ -163,22 +226,51 @@ impl Pass {
 
     /// Split string into an argument list.
     pub fn split_args(s: &str) -> Result<Vec<String>, ParseError> {
+        Self::split_args_spanned(s)
+            .map(|args| args.into_iter().map(|(_, arg)| arg).collect())
+            .map_err(|(_, reason)| reason)
+    }
+
+    /// Split string into an argument list, with whether each argument is quoted.
+    /// - Quoted arguments are always read as strings, so `"null"` is not null.
+    pub fn split_args_quoted(s: &str) -> Result<Vec<QuotedArg>, ParseError> {
+        Self::split_args_spanned(s)
+            .map(|args| {
+                args.into_iter()
+                    .map(|(range, arg)| (arg, s[range].contains(QUOTES)))
+                    .collect()
+            })
+            .map_err(|(_, reason)| reason)
+    }
+
+    /// Split string into an argument list, with the byte range each argument is written in.
+    /// - Errors come with the byte index they occurred at.
+    pub fn split_args_spanned(s: &str) -> Result<Vec<SpannedArg>, (usize, ParseError)> {
         let mut buf = String::new();
         let mut args = Vec::new();
 
+        let mut start = None;
         let mut in_string = None;
         let mut just_ended_string = false;
+        let mut quoted = false;
         let mut escaped = false;
 
-        for c in s.chars() {
-            if c == '\\' {
+        for (i, c) in s.char_indices() {
+            if start.is_none() && (c != ' ' || in_string.is_some()) {
+                start = Some(i);
+            }
+            if c == '\\' && !escaped {
                 escaped = true;
                 continue;
             } else if escaped {
-                if c == ' ' && in_string.is_none() {
-                    return Err(ParseError::IllegalArgument);
+                escaped = false;
+                if in_string.is_none() {
+                    if c == ' ' || just_ended_string {
+                        return Err((i, ParseError::IllegalArgument));
+                    }
+                    buf.push(c);
+                    continue;
                 }
-                escaped = false
             } else if let Some(quote) = in_string {
                 if quote == c {
                     in_string = None;
@@ -188,31 +280,43 @@ impl Pass {
             }
             match c {
                 _ if in_string.is_some() => buf.push(c),
+                ' ' if quoted => {
+                    args.push((start.take().unwrap_or(i)..i, std::mem::take(&mut buf)));
+                    just_ended_string = false;
+                    quoted = false;
+                }
                 ' ' => {
                     let push = buf.trim().to_string();
                     if push.is_empty() {
                         buf = push;
                         continue;
                     }
-                    args.push(push);
+                    args.push((start.take().unwrap_or(i)..i, push));
                     buf = String::new();
                     just_ended_string = false;
                 }
-                _ if just_ended_string => return Err(ParseError::IllegalArgument),
-                _ if QUOTES.contains(&c) => in_string = Some(c),
+                _ if just_ended_string => return Err((i, ParseError::IllegalArgument)),
+                _ if QUOTES.contains(&c) => {
+                    in_string = Some(c);
+                    quoted = true;
+                }
                 _ => buf.push(c),
             }
         }
 
-        if !buf.is_empty() {
-            args.push(buf);
+        if !buf.is_empty() || quoted {
+            args.push((start.unwrap_or(s.len())..s.len(), buf));
         }
 
         Ok(args)
     }
 
-    /// Split file content into two streams.
+    /// Split file content into three streams.
     pub fn parse(file: &Path, s: &str) -> Result<Self, Error> {
+        Self::parse_source(file, s, false)
+    }
+
+    fn parse_source(file: &Path, s: &str, on_disk: bool) -> Result<Self, Error> {
         let filename = if file.extension() == Some(OsStr::new("rules")) {
             file.file_stem()
                 .unwrap_or(OsStr::new("unnamed"))
@@ -221,7 +325,7 @@ impl Pass {
         } else {
             file.to_string_lossy().to_string()
         };
-        let s = match Self::clear_comment(s) {
+        let cleared = match Self::clear_comment(s) {
             Ok(s) => s,
             Err((line, reason)) => {
                 return Err(Error::ParseError {
@@ -233,10 +337,17 @@ impl Pass {
         };
 
         let mut scope = None;
+        let mut enums = Vec::new();
         let mut schema = Vec::new();
         let mut records = Vec::new();
-
-        for (mut line, no) in s.lines().zip(1_u32..) {
+        let mut sources = vec![Source::new(
+            filename.clone(),
+            file.to_path_buf(),
+            s.to_string(),
+            on_disk,
+        )];
+
+        for (mut line, no) in cleared.lines().zip(1_u32..) {
             line = line.trim();
 
             if line.is_empty() {
@@ -268,7 +379,7 @@ impl Pass {
                 continue;
             }
 
-            let words = match Self::split_args(line) {
+            let quoted = match Self::split_args_quoted(line) {
                 Ok(a) => a,
                 Err(e) => {
                     return Err(Error::ParseError {
@@ -278,6 +389,10 @@ impl Pass {
                     })
                 }
             };
+            let words = quoted
+                .iter()
+                .map(|(arg, _)| arg.clone())
+                .collect::<Vec<_>>();
 
             match words[0].to_lowercase().as_str() {
                 "include" if words.len() == 1 => {
@@ -300,9 +415,40 @@ impl Pass {
                         .unwrap_or(Path::new(""))
                         .join(words[1].as_str());
                     let mut loaded = Self::load(&new_path, &filename, no, false)?;
+                    loaded.sources[0].set_included_at(filename.clone(), no);
+                    enums.append(&mut loaded.enums);
                     schema.append(&mut loaded.schema);
                     records.append(&mut loaded.records);
+                    for source in loaded.sources {
+                        if !sources.iter().any(|s| s.name() == source.name()) {
+                            sources.push(source);
+                        }
+                    }
+                }
+                "enum" if words.len() < 3 => {
+                    return Err(Error::ParseError {
+                        location: filename,
+                        line: no,
+                        reason: ParseError::MissingArguments,
+                    })
+                }
+                "enum" if words[2] != "=" => {
+                    return Err(Error::ParseError {
+                        location: filename,
+                        line: no,
+                        reason: ParseError::IllegalArgument,
+                    })
                 }
+                "enum" => enums.push((
+                    if let Some(scope) = &scope {
+                        format!("{filename}<{scope}>")
+                    } else {
+                        filename.clone()
+                    },
+                    no,
+                    words[1].clone(),
+                    words[3..].to_vec(),
+                )),
                 "scope" if words.len() == 1 => scope = None,
                 "scope" if words.len() == 2 => scope = Some(words[1].clone()),
                 "scope" => {
@@ -320,12 +466,17 @@ impl Pass {
                     },
                     no,
                     label.to_string(),
-                    words[1..].to_vec(),
+                    quoted[1..].to_vec(),
                 )),
             }
         }
 
-        Ok(Self { schema, records })
+        Ok(Self {
+            enums,
+            schema,
+            records,
+            sources,
+        })
     }
 
     /// Read and split a file into streams.
@@ -365,6 +516,6 @@ impl Pass {
             }
         };
 
-        Self::parse(&to_load, &s)
+        Self::parse_source(&to_load, &s, true)
     }
 }
@@ -0,0 +1,158 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Bound, RangeBounds},
+};
+
+use crate::{constraint::write_range, Constraint, Error, Field, Signature};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Represents a value in a record that matches many values, in columns declared with `pattern`.
+///
+/// ```text
+/// acl:    host:istr pattern   port:u16 pattern    action:istr
+/// acl     *.example.com       443                 allow
+/// acl     *                   1000..2000          deny
+/// ```
+pub enum Pattern {
+    /// Any value including null, `*`.
+    Any,
+    /// String where `*` is any characters and `?` is one character, `*.example.com`.
+    Glob(String),
+    /// Value is within a range, `1000..2000`, `1000..=2000`, `1000..`.
+    Range(Bound<Field>, Bound<Field>),
+}
+
+impl Pattern {
+    /// Check if a value is matched by the pattern.
+    pub fn matches(&self, field: &Field) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Glob(pattern) => Constraint::glob_field(pattern, field),
+            Self::Range(start, end) => {
+                !field.is_null() && (start.as_ref(), end.as_ref()).contains(field)
+            }
+        }
+    }
+
+    /// Parse a pattern for a column of the type, `None` if the word is a plain value.
+    /// - `*` is any value.
+    /// - Words with `*` or `?` are globs in istr and ustr columns.
+    /// - Words written as ranges are ranges in columns of other ordered types.
+    pub(crate) fn parse(
+        sig: &Signature,
+        s: &str,
+        location: &str,
+        line: u32,
+    ) -> Result<Option<Self>, Error> {
+        Ok(match sig {
+            _ if s == "*" => Some(Self::Any),
+            Signature::IStr | Signature::UStr => {
+                s.contains(['*', '?']).then(|| Self::Glob(s.to_string()))
+            }
+            _ if sig.ordered() && Constraint::is_range(s) => {
+                match Constraint::range(s, sig, location, line) {
+                    Ok((start, end)) => Some(Self::Range(start, end)),
+                    Err(reason) => {
+                        return Err(Error::ParseError {
+                            location: location.to_string(),
+                            line,
+                            reason,
+                        })
+                    }
+                }
+            }
+            _ => None,
+        })
+    }
+
+    /// Convert the pattern to match values of the type, `None` if it cannot.
+    pub(crate) fn cast(&self, sig: &Signature) -> Option<Self> {
+        Some(match self {
+            Self::Any => Self::Any,
+            Self::Glob(pattern) if matches!(sig, Signature::IStr | Signature::UStr) => {
+                Self::Glob(pattern.clone())
+            }
+            Self::Range(start, end) if sig.ordered() => {
+                let cast = |bound: &Bound<Field>| -> Option<Bound<Field>> {
+                    Some(match bound {
+                        Bound::Included(field) => Bound::Included(sig.cast(field)?),
+                        Bound::Excluded(field) => Bound::Excluded(sig.cast(field)?),
+                        Bound::Unbounded => Bound::Unbounded,
+                    })
+                };
+                Self::Range(cast(start)?, cast(end)?)
+            }
+            _ => return None,
+        })
+    }
+
+    /// Converts bounds of ranges to the most generic type, see `Field::upcast`.
+    pub fn upcast(&self) -> Self {
+        match self {
+            Self::Range(start, end) => Self::Range(
+                start.as_ref().map(Field::upcast),
+                end.as_ref().map(Field::upcast),
+            ),
+            _ => self.clone(),
+        }
+    }
+
+    /// Returns number of characters a glob matches exactly, 0 for other patterns.
+    pub fn literal_len(&self) -> usize {
+        match self {
+            Self::Glob(pattern) => pattern.chars().filter(|c| !matches!(c, '*' | '?')).count(),
+            _ => 0,
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Any => 0,
+            Self::Glob(_) => 1,
+            Self::Range(..) => 2,
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Any => f.write_str("*"),
+            Self::Glob(pattern) => f.write_str(pattern),
+            Self::Range(start, end) => write_range(
+                f,
+                start.as_ref().map(Field::to_rules_string),
+                end.as_ref().map(Field::to_rules_string),
+            ),
+        }
+    }
+}
+
+impl PartialOrd for Pattern {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Patterns of the same kind are compared by value, ranges by start then end.
+impl Ord for Pattern {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Glob(a), Self::Glob(b)) => a.cmp(b),
+            (Self::Range(a, b), Self::Range(c, d)) => {
+                (bound_key(a), bound_key(b)).cmp(&(bound_key(c), bound_key(d)))
+            }
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+/// Orders unbounded before included before excluded, then by value.
+fn bound_key(bound: &Bound<Field>) -> (u8, Option<&Field>) {
+    match bound {
+        Bound::Unbounded => (0, None),
+        Bound::Included(field) => (1, Some(field)),
+        Bound::Excluded(field) => (2, Some(field)),
+    }
+}
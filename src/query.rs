@@ -0,0 +1,428 @@
+use crate::{
+    Condition, Document, Error, Field, Filter, ParseError, Pass, Predicate, QueryError, Record,
+    SchemaOne, Signature, QUOTES,
+};
+
+/// Operators and brackets, longer ones first.
+const PUNCTS: &[&str] = &["(", ")", "|", "!=", ">=", "<=", "=", ">", "<"];
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// Represents a parsed query.
+///
+/// A query is a record type followed by a condition, and stages separated by `|`.
+/// - `whitelist * 127.0.0.1 *` has a value or `*` for each field, like a record.
+/// - `whitelist 0="joe" 2>1000` compares fields by column index, or by name as in `expiry>1000`.
+/// - Comparisons are `=`, `!=`, `>`, `>=`, `<` and `<=`, combined with `and`, `or`, `not` and brackets.
+/// - `expiry=null` and `expiry!=null` check for null in nullable columns.
+/// - `ports has 443` matches lists containing the value, and `net has 10.0.3.7` networks containing the address.
+/// - An address compared equal to a cidr column, as in `net=10.0.3.7`, also matches networks containing it.
+/// - `| sort 2 desc 0 asc` orders results by columns, ascending unless `desc`.
+/// - `| offset 10` skips results, and `| limit 10` caps the number of results.
+///
+/// Arguments are quoted and escaped as in a rules file.
+pub struct Query {
+    filter: Filter,
+}
+
+impl Query {
+    /// Parse a query against the definitions of a document.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// # let doc = Options::default().with("whitelist: ustr istr u64").open().unwrap();
+    /// let query = Query::parse(&doc, r#"whitelist 0="joe" 2>1000 | sort 2 | limit 10"#).unwrap();
+    /// let found = query.run(&doc).unwrap();
+    /// ```
+    pub fn parse(doc: &Document, s: &str) -> Result<Self, Error> {
+        let mut parser = Parser {
+            s,
+            tokens: lex(s)?,
+            pos: 0,
+        };
+
+        let label = match parser.peek() {
+            Some(Token::Text(label, _)) => label.clone(),
+            _ => return Err(parser.expected("record type")),
+        };
+        let schema = doc.schema(&label).ok_or(Error::NoDefinition {
+            label: label.clone(),
+        })?;
+        parser.pos += 1;
+
+        let mut filter = Filter::with_condition(label, parser.body(schema)?);
+
+        while parser.peek().is_some() {
+            if parser.peek() != Some(&Token::Punct("|")) {
+                return Err(parser.expected("`|`"));
+            }
+            parser.pos += 1;
+
+            if parser.keyword("sort") {
+                loop {
+                    let column = parser.column(schema)?;
+                    let desc = parser.keyword("desc");
+                    if !desc {
+                        parser.keyword("asc");
+                    }
+                    filter = filter.with_sort(column, desc);
+                    if !matches!(parser.peek(), Some(Token::Text(..))) {
+                        break;
+                    }
+                }
+            } else if parser.keyword("offset") {
+                filter = filter.with_offset(parser.number()?);
+            } else if parser.keyword("limit") {
+                filter = filter.with_limit(parser.number()?);
+            } else {
+                return Err(match parser.peek() {
+                    Some(Token::Text(got, _)) => parser.error(QueryError::UnknownStage {
+                        got: got.to_string(),
+                    }),
+                    _ => parser.expected("stage"),
+                });
+            }
+        }
+
+        Ok(Self { filter })
+    }
+
+    /// Returns the filter matching records.
+    pub fn filter(&self) -> &Filter {
+        &self.filter
+    }
+
+    /// Returns columns to order by, `true` for descending.
+    pub fn sort(&self) -> &[(usize, bool)] {
+        self.filter.sort()
+    }
+
+    /// Returns number of results skipped.
+    pub fn offset(&self) -> usize {
+        self.filter.offset()
+    }
+
+    /// Returns maximum number of results.
+    pub fn limit(&self) -> Option<usize> {
+        self.filter.limit()
+    }
+
+    /// Run the query and return the results.
+    pub fn run<'a>(&self, doc: &'a Document) -> Result<Vec<&'a Record>, Error> {
+        doc.find(self.filter.clone())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Token {
+    /// Argument with quotes removed, and whether it was quoted.
+    Text(String, bool),
+    /// Operator or bracket.
+    Punct(&'static str),
+}
+
+/// Split query into tokens with their byte index, with the same quoting rules as `Pass::split_args`.
+fn lex(s: &str) -> Result<Vec<(usize, Token)>, Error> {
+    let words = Pass::split_args_spanned(s)
+        .map_err(|(i, reason)| error(s, i, QueryError::ParseError(reason)))?;
+    let mut tokens = Vec::new();
+
+    for (range, _) in words {
+        let raw = &s[range.clone()];
+        let mut start = 0;
+        let mut i = 0;
+        let mut in_string = None;
+        let mut escaped = false;
+
+        while let Some(c) = raw[i..].chars().next() {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if let Some(quote) = in_string {
+                if quote == c {
+                    in_string = None;
+                }
+            } else if QUOTES.contains(&c) {
+                in_string = Some(c);
+            } else if let Some(punct) = PUNCTS.iter().find(|p| raw[i..].starts_with(**p)) {
+                push_text(&mut tokens, s, range.start + start, &raw[start..i])?;
+                tokens.push((range.start + i, Token::Punct(punct)));
+                i += punct.len();
+                start = i;
+                continue;
+            }
+
+            i += c.len_utf8();
+        }
+
+        push_text(&mut tokens, s, range.start + start, &raw[start..])?;
+    }
+
+    Ok(tokens)
+}
+
+fn push_text(tokens: &mut Vec<(usize, Token)>, s: &str, at: usize, raw: &str) -> Result<(), Error> {
+    if raw.is_empty() {
+        return Ok(());
+    }
+
+    let text = Pass::split_args(raw)
+        .map_err(|reason| error(s, at, QueryError::ParseError(reason)))?
+        .concat();
+    tokens.push((at, Token::Text(text, raw.contains(QUOTES))));
+    Ok(())
+}
+
+fn error(s: &str, at: usize, reason: QueryError) -> Error {
+    Error::QueryError {
+        column: s[..at].chars().count() + 1,
+        reason,
+    }
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    /// Returns true and advances if the next token is the unquoted keyword.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Text(text, false)) if text == keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Error at the next token.
+    fn error(&self, reason: QueryError) -> Error {
+        let at = self
+            .tokens
+            .get(self.pos)
+            .map_or(self.s.len(), |(at, _)| *at);
+        error(self.s, at, reason)
+    }
+
+    fn expected(&self, expected: &str) -> Error {
+        self.error(QueryError::Expected {
+            expected: expected.to_string(),
+            got: self.peek().map(|token| match token {
+                Token::Text(text, _) => text.to_string(),
+                Token::Punct(punct) => punct.to_string(),
+            }),
+        })
+    }
+
+    /// Condition before the first stage.
+    fn body(&mut self, schema: &SchemaOne) -> Result<Condition, Error> {
+        let end = self.tokens[self.pos..]
+            .iter()
+            .position(|(_, token)| token == &Token::Punct("|"))
+            .map_or(self.tokens.len(), |n| self.pos + n);
+
+        if self.pos == end {
+            return Ok(Condition::And(Vec::new()));
+        }
+
+        let positional = self.tokens[self.pos..end].iter().all(|(_, token)| {
+            matches!(token, Token::Text(text, quoted) if *quoted || !["and", "or", "not", "has"].contains(&text.as_str()))
+        });
+
+        if !positional {
+            let cond = self.or(schema)?;
+            return match self.peek() {
+                None | Some(Token::Punct("|")) => Ok(cond),
+                _ => Err(self.expected("condition")),
+            };
+        }
+
+        if end - self.pos != schema.sig().len() {
+            return Err(
+                self.error(QueryError::ParseError(ParseError::ArgumentLengthMismatch {
+                    expected: schema.sig().len(),
+                    got: end - self.pos,
+                })),
+            );
+        }
+
+        let mut preds = Vec::with_capacity(schema.sig().len());
+        for column in 0..schema.sig().len() {
+            preds.push(self.value(schema, column)?);
+        }
+
+        Ok(Condition::Fields(preds))
+    }
+
+    fn or(&mut self, schema: &SchemaOne) -> Result<Condition, Error> {
+        let mut conds = vec![self.and(schema)?];
+        while self.keyword("or") {
+            conds.push(self.and(schema)?);
+        }
+
+        Ok(match conds.len() {
+            1 => conds.remove(0),
+            _ => Condition::Or(conds),
+        })
+    }
+
+    fn and(&mut self, schema: &SchemaOne) -> Result<Condition, Error> {
+        let mut conds = vec![self.unary(schema)?];
+        loop {
+            match self.peek() {
+                None | Some(Token::Punct(")" | "|")) => break,
+                Some(Token::Text(text, false)) if text == "or" => break,
+                _ => {
+                    self.keyword("and");
+                    conds.push(self.unary(schema)?);
+                }
+            }
+        }
+
+        Ok(match conds.len() {
+            1 => conds.remove(0),
+            _ => Condition::And(conds),
+        })
+    }
+
+    fn unary(&mut self, schema: &SchemaOne) -> Result<Condition, Error> {
+        if self.keyword("not") {
+            return Ok(!self.unary(schema)?);
+        }
+
+        match self.peek() {
+            Some(Token::Punct("(")) => {
+                self.pos += 1;
+                let cond = self.or(schema)?;
+                if self.peek() != Some(&Token::Punct(")")) {
+                    return Err(self.expected("`)`"));
+                }
+                self.pos += 1;
+                Ok(cond)
+            }
+            Some(Token::Text(..)) => {
+                let column = self.column(schema)?;
+                let contained = match &schema.sig()[column] {
+                    Signature::Cidr => Some(&Signature::Cidr),
+                    sig => sig.element(),
+                };
+                if let Some(sig) = contained {
+                    if self.keyword("has") {
+                        return Ok(Condition::Field(
+                            column,
+                            Predicate::Contains(self.field(sig)?),
+                        ));
+                    }
+                }
+
+                let op = match self.peek() {
+                    Some(Token::Punct(op @ ("=" | "!=" | ">" | ">=" | "<" | "<="))) => *op,
+                    _ => return Err(self.expected("operator")),
+                };
+                self.pos += 1;
+
+                let value = match self.value(schema, column)? {
+                    Predicate::Eq(value) => value,
+                    Predicate::IsNull if op == "!=" => {
+                        return Ok(Condition::Field(column, Predicate::NotNull))
+                    }
+                    pred if op == "=" => return Ok(Condition::Field(column, pred)),
+                    _ => {
+                        self.pos -= 1;
+                        return Err(self.expected("value"));
+                    }
+                };
+
+                Ok(Condition::Field(
+                    column,
+                    match op {
+                        "=" => Predicate::Eq(value),
+                        "!=" => Predicate::Ne(value),
+                        ">" => Predicate::Gt(value),
+                        ">=" => Predicate::Ge(value),
+                        "<" => Predicate::Lt(value),
+                        _ => Predicate::Le(value),
+                    },
+                ))
+            }
+            _ => Err(self.expected("condition")),
+        }
+    }
+
+    /// Column reference by index or name.
+    fn column(&mut self, schema: &SchemaOne) -> Result<usize, Error> {
+        let got = match self.peek() {
+            Some(Token::Text(got, _)) => got.to_string(),
+            _ => return Err(self.expected("column")),
+        };
+
+        match got.parse::<usize>().ok().or_else(|| schema.position(&got)) {
+            Some(column) if column < schema.sig().len() => {
+                self.pos += 1;
+                Ok(column)
+            }
+            _ => Err(self.error(QueryError::UnknownColumn { got })),
+        }
+    }
+
+    /// Value of a column, `*` for any value.
+    fn value(&mut self, schema: &SchemaOne, column: usize) -> Result<Predicate, Error> {
+        let pred = match self.peek() {
+            Some(Token::Text(text, false)) if text == "*" => Predicate::Any,
+            Some(Token::Text(text, false))
+                if text == "null" && schema.columns()[column].nullable() =>
+            {
+                Predicate::IsNull
+            }
+            Some(Token::Text(text, _)) => {
+                match Predicate::parse(&schema.sig()[column], text, "query") {
+                    Ok(pred) => pred,
+                    Err(Error::ParseError { reason, .. }) => {
+                        return Err(self.error(QueryError::ParseError(reason)))
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            _ => return Err(self.expected("value")),
+        };
+
+        self.pos += 1;
+        Ok(pred)
+    }
+
+    /// Value of the type.
+    fn field(&mut self, sig: &Signature) -> Result<Field, Error> {
+        let field = match self.peek() {
+            Some(Token::Text(text, _)) => match sig.parse(text, "query", 0) {
+                Ok(value) => value,
+                Err(Error::ParseError { reason, .. }) => {
+                    return Err(self.error(QueryError::ParseError(reason)))
+                }
+                Err(e) => return Err(e),
+            },
+            _ => return Err(self.expected("value")),
+        };
+
+        self.pos += 1;
+        Ok(field)
+    }
+
+    fn number(&mut self) -> Result<usize, Error> {
+        match self.peek() {
+            Some(Token::Text(text, _)) => match text.parse() {
+                Ok(n) => {
+                    self.pos += 1;
+                    Ok(n)
+                }
+                Err(_) => Err(self.expected("number")),
+            },
+            _ => Err(self.expected("number")),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::{fmt::Display, sync::Arc};
 
 use crate::Field;
 
@@ -9,6 +9,8 @@ pub struct Record {
     location: String,
     line: u32,
     args: Vec<Field>,
+    // column names of the definition, shared by records of the same type
+    names: Arc<[Option<String>]>,
 }
 
 impl Record {
@@ -19,9 +21,16 @@ impl Record {
             location,
             line,
             args,
+            names: Arc::from([]),
         }
     }
 
+    /// Attach column names of the definition.
+    pub(crate) fn with_names(mut self, names: Arc<[Option<String>]>) -> Self {
+        self.names = names;
+        self
+    }
+
     /// Returns rule label.
     pub fn label(&self) -> &str {
         &self.label
@@ -32,6 +41,25 @@ impl Record {
         &self.args
     }
 
+    /// Returns the field in the column with the name.
+    ///
+    /// ```
+    /// # use simplerecords::*;
+    /// # let doc = Options::default()
+    /// #     .with("whitelist: name:ustr ip:istr expiry:u64")
+    /// #     .with("whitelist joe 127.0.0.1 123456")
+    /// #     .open()
+    /// #     .unwrap();
+    /// let record = doc.find_one(filter!("whitelist"; "joe", *, *)).unwrap().unwrap();
+    /// assert_eq!(record.get("ip"), Some(&Field::from("127.0.0.1")));
+    /// ```
+    pub fn get(&self, name: &str) -> Option<&Field> {
+        self.names
+            .iter()
+            .position(|column| column.as_deref() == Some(name))
+            .and_then(|column| self.args.get(column))
+    }
+
     /// Returns file location.
     pub fn location(&self) -> &str {
         &self.location
@@ -41,6 +69,14 @@ impl Record {
     pub fn line(&self) -> u32 {
         self.line
     }
+
+    /// Converts record to how it is written in a rules file.
+    pub fn to_rules_string(&self) -> String {
+        std::iter::once(self.label.clone())
+            .chain(self.args.iter().map(Field::to_rules_string))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
 }
 
 impl Display for Record {
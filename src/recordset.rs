@@ -1,13 +1,30 @@
 // pub struct Records()
 
-use std::collections::HashMap;
+use std::{
+    collections::{btree_map, btree_set, hash_map, BTreeMap, BTreeSet, HashMap},
+    fmt::Display,
+    iter::{Enumerate, Peekable},
+    slice,
+    sync::Arc,
+    vec,
+};
 
-use crate::{Error, Field, Filter, ParseError, Record, Schema, SchemaOne};
+use crate::{
+    trie::{network, PrefixTrie},
+    Aggregate, Aggregator, Condition, Error, Field, Filter, Group, ParseError, Pattern, Predicate,
+    QuotedArg, Record, Schema, SchemaOne, Signature, NULL,
+};
 
 #[derive(Debug)]
 /// Represents all records of the same type.
 pub struct RecordSet {
     tree: RecordTree,
+    // ordered by declaration, inserted records come last
+    records: Vec<Option<Record>>,
+    len: usize,
+    indexes: BTreeMap<usize, HashMap<Field, BTreeSet<usize>>>,
+    // column names, attached to every record put in the set
+    names: Arc<[Option<String>]>,
     schema: SchemaOne,
 }
 
@@ -16,34 +33,306 @@ impl RecordSet {
     pub fn new(schema: SchemaOne) -> Self {
         Self {
             tree: RecordTree::Void,
+            records: Vec::new(),
+            len: 0,
+            indexes: schema
+                .columns()
+                .iter()
+                .enumerate()
+                .filter(|(_, column)| column.indexed())
+                .map(|(i, _)| (i, HashMap::new()))
+                .collect(),
+            names: schema
+                .columns()
+                .iter()
+                .map(|column| column.name().map(str::to_string))
+                .collect(),
             schema,
         }
     }
 
-    /// Puts record into set.
+    /// Puts record into set, returns the replaced record with the same values.
     pub fn put(&mut self, record: Record) -> Option<Record> {
-        match &mut self.tree {
-            RecordTree::Void if record.args().is_empty() => {
-                self.tree = RecordTree::Unit(record);
+        let id = self.records.len();
+        self.records
+            .push(Some(record.with_names(self.names.clone())));
+        self.attach(id).and_then(|old| self.records[old].take())
+    }
+
+    /// Returns type schema.
+    pub fn schema(&self) -> &SchemaOne {
+        &self.schema
+    }
+
+    /// Returns number of records in set.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no records in set.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns all records in set, in the order they are declared.
+    pub fn records(&self) -> Vec<&Record> {
+        self.records.iter().flatten().collect()
+    }
+
+    /// Inserts a new record, fields are converted to the types of the definition.
+    pub fn insert(&mut self, fields: Vec<Field>) -> Result<(), Error> {
+        let args = match self.schema.cast(&fields) {
+            Some(args) => args,
+            None => {
+                return Err(Error::RecordMismatch {
+                    expected: Box::new(self.schema.clone()),
+                    got: fields,
+                })
+            }
+        };
+
+        self.duplicate(&args)?;
+        self.put(Record::new(
+            self.schema.label().to_string(),
+            self.schema.location().to_string(),
+            0,
+            args,
+        ));
+        Ok(())
+    }
+
+    /// Removes and returns all records matching filter.
+    pub fn remove(&mut self, filter: Filter) -> Result<Vec<Record>, Error> {
+        let ids = self.find_ids(filter)?;
+
+        Ok(ids
+            .into_iter()
+            .filter_map(|id| {
+                self.detach(id);
+                self.records[id].take()
+            })
+            .collect())
+    }
+
+    /// Amends all records matching filter, `None` leaves the field unchanged.
+    /// Returns the number of records changed.
+    pub fn update(&mut self, filter: Filter, changes: &[Option<Field>]) -> Result<usize, Error> {
+        let cast = (changes.len() == self.schema.sig().len())
+            .then(|| {
+                changes
+                    .iter()
+                    .enumerate()
+                    .map(|(column, change)| match change {
+                        Some(field) => self.schema.cast_one(column, field).map(Some),
+                        None => Some(None),
+                    })
+                    .collect::<Option<Vec<_>>>()
+            })
+            .flatten();
+
+        let changes = match cast {
+            Some(cast) => cast,
+            _ => {
+                return Err(Error::RecordMismatch {
+                    expected: Box::new(self.schema.clone()),
+                    got: changes.iter().flatten().cloned().collect(),
+                })
+            }
+        };
+
+        let ids = self.find_ids(filter)?;
+        ids.iter().for_each(|id| self.detach(*id));
+        let mut old = Vec::with_capacity(ids.len());
+
+        for (n, id) in ids.iter().enumerate() {
+            let record = self.records[*id].take().unwrap();
+            let args = record
+                .args()
+                .iter()
+                .zip(changes.iter())
+                .map(|(old, new)| new.as_ref().unwrap_or(old).clone())
+                .collect::<Vec<_>>();
+
+            if let Err(e) = self.duplicate(&args) {
+                ids[..n].iter().for_each(|id| self.detach(*id));
+                self.records[*id] = Some(record);
+                for (id, record) in ids.iter().zip(old) {
+                    self.records[*id] = Some(record);
+                }
+                ids.iter().for_each(|id| {
+                    self.attach(*id);
+                });
+                return Err(e);
+            }
+
+            self.records[*id] = Some(
+                Record::new(
+                    record.label().to_string(),
+                    record.location().to_string(),
+                    record.line(),
+                    args,
+                )
+                .with_names(self.names.clone()),
+            );
+            self.attach(*id);
+            old.push(record);
+        }
+
+        Ok(ids.len())
+    }
+
+    /// Adds a secondary index on a column, so filters on it do not walk every branch.
+    pub fn create_index(&mut self, column: usize) -> Result<(), Error> {
+        self.column(column)?;
+
+        let mut index: HashMap<Field, BTreeSet<usize>> = HashMap::new();
+        for (id, record) in self.records.iter().enumerate() {
+            if let Some(record) = record {
+                for key in index_keys(&record.args()[column]) {
+                    index.entry(key).or_default().insert(id);
+                }
+            }
+        }
+
+        self.indexes.insert(column, index);
+        self.schema.set_indexed(column, true);
+        Ok(())
+    }
+
+    /// Removes the secondary index on a column.
+    pub fn drop_index(&mut self, column: usize) -> Result<(), Error> {
+        self.column(column)?;
+        self.indexes.remove(&column);
+        self.schema.set_indexed(column, false);
+        Ok(())
+    }
+
+    /// Returns how records matching filter would be found.
+    pub fn explain(&self, filter: Filter) -> Result<Plan, Error> {
+        let filter = self.prepare(filter)?;
+        Ok(self.plan(&filter.condition().narrow(self.schema.sig().len())))
+    }
+
+    /// Fails if column is out of range.
+    fn column(&self, column: usize) -> Result<(), Error> {
+        if column < self.schema.sig().len() {
+            Ok(())
+        } else {
+            Err(Error::UnknownColumn {
+                label: self.schema.label().to_string(),
+                column: column.to_string(),
+            })
+        }
+    }
+
+    /// Fails if an entry with the same values already exists.
+    fn duplicate(&self, args: &[Field]) -> Result<(), Error> {
+        let preds = args
+            .iter()
+            .map(|arg| Predicate::Eq(arg.upcast()))
+            .collect::<Vec<_>>();
+
+        match Walk::new(&self.tree, preds).next() {
+            None => Ok(()),
+            Some(id) => {
+                let existing = self.records[id].as_ref().unwrap();
+                Err(Error::DuplicatedEntry {
+                    first_appear: existing.location().to_string(),
+                    line: existing.line(),
+                    label: existing.label().to_string(),
+                })
+            }
+        }
+    }
+
+    /// Adds record with id to the tree and indexes, returns the id of the replaced record.
+    fn attach(&mut self, id: usize) -> Option<usize> {
+        let args = self.records[id].as_ref().unwrap().args().to_vec();
+        let sig = self.schema.sig();
+
+        let replaced = match &mut self.tree {
+            RecordTree::Void if args.is_empty() => {
+                self.tree = RecordTree::Unit(id);
                 None
             }
             RecordTree::Void => {
-                self.tree = RecordTree::Index(HashMap::new());
-                self.tree.put(record.clone().args(), record)
+                self.tree = RecordTree::branch(&sig[0]);
+                self.tree.put(&args, sig, id)
+            }
+            t => t.put(&args, sig, id),
+        };
+
+        match replaced {
+            Some(old) => self.unindex(old, &args),
+            None => self.len += 1,
+        }
+        for (column, index) in self.indexes.iter_mut() {
+            for key in index_keys(&args[*column]) {
+                index.entry(key).or_default().insert(id);
             }
-            t => t.put(record.args().to_vec().as_slice(), record),
         }
+
+        replaced
     }
 
-    /// Returns type schema.
-    pub fn schema(&self) -> &SchemaOne {
-        &self.schema
+    /// Removes record with id from the tree and indexes, the record is kept.
+    fn detach(&mut self, id: usize) {
+        let args = self.records[id].as_ref().unwrap().args().to_vec();
+
+        let removed = match &mut self.tree {
+            RecordTree::Void => None,
+            RecordTree::Unit(_) => match std::mem::replace(&mut self.tree, RecordTree::Void) {
+                RecordTree::Unit(id) => Some(id),
+                _ => unreachable!("wrong length"),
+            },
+            t => t.remove(&args),
+        };
+
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        self.unindex(id, &args);
+    }
+
+    fn unindex(&mut self, id: usize, args: &[Field]) {
+        for (column, index) in self.indexes.iter_mut() {
+            for key in index_keys(&args[*column]) {
+                if let Some(ids) = index.get_mut(&key) {
+                    ids.remove(&id);
+                    if ids.is_empty() {
+                        index.remove(&key);
+                    }
+                }
+            }
+        }
     }
 
     /// Parse record stream into collection of RecordSet.
+    /// - Arguments are read as unquoted, see `parse_quoted`.
     pub fn parse(
         schema: Schema,
         entries: Vec<(String, u32, String, Vec<String>)>,
+    ) -> Result<HashMap<String, RecordSet>, Error> {
+        Self::parse_quoted(
+            schema,
+            entries
+                .into_iter()
+                .map(|(location, no, label, args)| {
+                    (
+                        location,
+                        no,
+                        label,
+                        args.into_iter().map(|arg| (arg, false)).collect(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// Parse record stream into collection of RecordSet, with whether each argument is quoted.
+    pub fn parse_quoted(
+        schema: Schema,
+        entries: Vec<(String, u32, String, Vec<QuotedArg>)>,
     ) -> Result<HashMap<String, RecordSet>, Error> {
         let mut parsed_entries = schema.as_template();
 
@@ -53,8 +342,10 @@ impl RecordSet {
                     label,
                     location.clone(),
                     no,
-                    recordset.schema().parse(
-                        args.iter().map(String::as_str).collect(),
+                    recordset.schema().parse_quoted(
+                        args.iter()
+                            .map(|(arg, quoted)| (arg.as_str(), *quoted))
+                            .collect(),
                         &location,
                         no,
                     )?,
@@ -84,112 +375,961 @@ impl RecordSet {
         Ok(parsed_entries)
     }
 
-    /// Find and returns the first result matching filter, order is not preserved.
-    pub fn find_one(&self, mut filter: Filter) -> Result<Option<&Record>, Error> {
-        filter.apply(self.schema());
+    /// Find and returns the first declared result matching filter.
+    pub fn find_one(&self, filter: Filter) -> Result<Option<&Record>, Error> {
+        let filter = self.prepare(filter)?;
+        let cond = filter.condition();
+        let preds = cond.narrow(self.schema.sig().len());
 
-        if !self.schema().match_filter(&filter) {
-            return Err(Error::FilterMismatch {
+        let id = match self.plan(&preds) {
+            Plan::Tree { .. }
+                if cond.exact()
+                    && filter.offset() == 0
+                    && filter.limit() != Some(0)
+                    && preds.iter().all(|p| matches!(p, Predicate::Eq(_))) =>
+            {
+                Walk::new(&self.tree, preds).next()
+            }
+            plan => {
+                let limit = filter.limit().unwrap_or(1).min(1);
+                self.page(self.candidates(plan, &filter), &filter.with_limit(limit))
+                    .into_iter()
+                    .next()
+            }
+        };
+
+        Ok(id.and_then(|id| self.records[id].as_ref()))
+    }
+
+    /// Find and returns all results matching filter, in the order they are declared.
+    pub fn find(&self, filter: Filter) -> Result<Vec<&Record>, Error> {
+        Ok(self
+            .find_ids(filter)?
+            .into_iter()
+            .filter_map(|id| self.records[id].as_ref())
+            .collect())
+    }
+
+    /// Find and returns results matching filter that are valid at the instant, see `SchemaOne::valid_at`.
+    pub fn find_at(&self, filter: Filter, instant: &Field) -> Result<Vec<&Record>, Error> {
+        let valid = self.schema.valid_at(instant);
+        self.find(filter.and(valid))
+    }
+
+    /// Returns records past their `not_after` or `expires` bounds at the instant, in the order they are declared.
+    pub fn expired(&self, now: &Field) -> Result<Vec<&Record>, Error> {
+        match self.schema.expired_at(now) {
+            Condition::Or(conds) if conds.is_empty() => Ok(Vec::new()),
+            cond => self.find(Filter::with_condition(
+                self.schema.label().to_string(),
+                cond,
+            )),
+        }
+    }
+
+    /// Returns records with patterns matching all values, most specific first.
+    /// - Values are concrete and given for the first columns, remaining columns match anything.
+    /// - Columns declared with `pattern` match values by their patterns, other fields by equality.
+    /// - A cidr field matches addresses and networks it contains.
+    /// - Records are ranked over the given columns by number of exact fields, then globs and ranges, then exact characters in globs and prefix lengths, ties in the order they are declared.
+    pub fn match_rules(&self, values: &[Field]) -> Result<Vec<&Record>, Error> {
+        let args = values
+            .iter()
+            .enumerate()
+            .map(|(column, value)| match value {
+                Field::Pattern(_) => None,
+                _ if column >= self.schema.sig().len() => None,
+                _ => self.schema.cast_one(column, value).map(|arg| arg.upcast()),
+            })
+            .collect::<Option<Vec<_>>>();
+        let Some(args) = args else {
+            return Err(Error::RecordMismatch {
                 expected: Box::new(self.schema.clone()),
-                got: filter.clone(),
+                got: values.to_vec(),
             });
+        };
+
+        let mut ids = Vec::new();
+        self.tree.rules(&args, &mut ids);
+
+        let mut found = ids
+            .into_iter()
+            .filter_map(|id| Some((id, self.records[id].as_ref()?)))
+            .map(|(id, record)| (Self::specificity(&record.args()[..args.len()]), id, record))
+            .collect::<Vec<_>>();
+        found.sort_by(|(a, i, _), (b, j, _)| b.cmp(a).then(i.cmp(j)));
+
+        Ok(found.into_iter().map(|(_, _, record)| record).collect())
+    }
+
+    /// Returns number of exact fields, number of globs and ranges, and exact characters in globs plus prefix lengths.
+    fn specificity(args: &[Field]) -> (usize, usize, usize) {
+        args.iter()
+            .fold((0, 0, 0), |(exact, patterns, len), arg| match arg {
+                Field::Pattern(pattern) if **pattern == Pattern::Any => (exact, patterns, len),
+                Field::Pattern(pattern) => (exact, patterns + 1, len + pattern.literal_len()),
+                Field::Cidr(_, prefix) => (exact + 1, patterns, len + *prefix as usize),
+                _ => (exact + 1, patterns, len),
+            })
+    }
+
+    fn find_ids(&self, filter: Filter) -> Result<Vec<usize>, Error> {
+        let filter = self.prepare(filter)?;
+        let plan = self.plan(&filter.condition().narrow(self.schema.sig().len()));
+        Ok(self.page(self.candidates(plan, &filter), &filter))
+    }
+
+    /// Orders and skips ids of matching records as specified by filter.
+    fn page(&self, mut ids: Vec<usize>, filter: &Filter) -> Vec<usize> {
+        let end = match filter.limit() {
+            Some(limit) => filter.offset().saturating_add(limit).min(ids.len()),
+            None => ids.len(),
+        };
+
+        if !filter.sort().is_empty() {
+            let cmp = |a: &usize, b: &usize| {
+                let (a_args, b_args) = (
+                    self.records[*a].as_ref().unwrap().args(),
+                    self.records[*b].as_ref().unwrap().args(),
+                );
+                filter
+                    .sort()
+                    .iter()
+                    .map(|(column, desc)| {
+                        let ordering = a_args[*column].cmp(&b_args[*column]);
+                        if *desc {
+                            ordering.reverse()
+                        } else {
+                            ordering
+                        }
+                    })
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or_else(|| a.cmp(b))
+            };
+
+            // only the first `end` results need to be sorted
+            if 0 < end && end < ids.len() {
+                ids.select_nth_unstable_by(end - 1, cmp);
+            }
+            ids.truncate(end);
+            ids.sort_unstable_by(cmp);
         }
 
-        Ok(self.tree.find_one(filter.args()))
+        ids.truncate(end);
+        ids.drain(..filter.offset().min(ids.len()));
+        ids
     }
 
-    /// Find and returns all results matching filter, order is not preserved.
-    pub fn find(&self, mut filter: Filter) -> Result<Vec<&Record>, Error> {
+    /// Converts filter to the types of the definition, fails if it does not fit.
+    fn prepare(&self, mut filter: Filter) -> Result<Filter, Error> {
         filter.apply(self.schema());
 
         if !self.schema().match_filter(&filter) {
             return Err(Error::FilterMismatch {
                 expected: Box::new(self.schema.clone()),
-                got: filter.clone(),
+                got: Box::new(filter),
+            });
+        }
+
+        Ok(filter)
+    }
+
+    /// Picks the plan expected to visit the fewest records.
+    fn plan(&self, preds: &[Predicate]) -> Plan {
+        let tree = Plan::Tree {
+            estimate: match preds.first() {
+                None => self.len,
+                Some(pred) => match Self::values(pred) {
+                    Some(values) => (values.len() * self.len)
+                        .div_ceil(self.tree.keys().max(1))
+                        .min(self.len),
+                    None if pred.bounds().is_none() => 0,
+                    None => match pred {
+                        Predicate::Any
+                        | Predicate::Ne(_)
+                        | Predicate::NotNull
+                        | Predicate::Contains(_)
+                        | Predicate::Not(_) => self.len,
+                        _ => self.len.div_ceil(3),
+                    },
+                },
+            },
+        };
+
+        self.indexes
+            .iter()
+            .filter_map(|(column, index)| {
+                Some(Plan::Index {
+                    column: *column,
+                    estimate: self
+                        .index_values(*column, &preds[*column])?
+                        .iter()
+                        .map(|value| index.get(value).map_or(0, BTreeSet::len))
+                        .sum(),
+                })
+            })
+            .fold(tree, |best, plan| {
+                if plan.estimate() < best.estimate() {
+                    plan
+                } else {
+                    best
+                }
+            })
+    }
+
+    /// Returns the values a predicate can match, `None` if it is not a list of values.
+    fn values(pred: &Predicate) -> Option<Vec<&Field>> {
+        match pred {
+            Predicate::Eq(value) => Some(vec![value]),
+            Predicate::IsNull => Some(vec![&NULL]),
+            Predicate::None => Some(Vec::new()),
+            Predicate::Or(preds) => preds
+                .iter()
+                .map(Self::values)
+                .collect::<Option<Vec<_>>>()
+                .map(|values| values.concat()),
+            _ => None,
+        }
+    }
+
+    /// Returns the keys of the index on a column a predicate can match.
+    /// - Elements for list columns.
+    /// - Every network containing the value for cidr columns.
+    fn index_values(&self, column: usize, pred: &Predicate) -> Option<Vec<Field>> {
+        let values = match self.schema.sig()[column] {
+            Signature::List(_) => Self::elements(pred),
+            Signature::Cidr => return Self::networks(pred),
+            _ => Self::values(pred),
+        };
+        values.map(|values| values.into_iter().cloned().collect())
+    }
+
+    /// Returns networks a predicate on a cidr column can match, `None` if it is not a list of networks.
+    fn networks(pred: &Predicate) -> Option<Vec<Field>> {
+        match pred {
+            Predicate::Contains(Field::Cidr(addr, len)) => Some(
+                (0..=*len)
+                    .map(|n| Field::Cidr(network(*addr, n), n))
+                    .collect(),
+            ),
+            Predicate::Or(preds) => preds
+                .iter()
+                .map(Self::networks)
+                .collect::<Option<Vec<_>>>()
+                .map(|values| values.concat()),
+            _ => Self::values(pred).map(|values| values.into_iter().cloned().collect()),
+        }
+    }
+
+    /// Returns elements one of which a list must contain to match the predicate, `None` if there are none.
+    fn elements(pred: &Predicate) -> Option<Vec<&Field>> {
+        match pred {
+            Predicate::Contains(value) => Some(vec![value]),
+            Predicate::Eq(Field::List(items)) => Some(vec![items.first()?]),
+            Predicate::None => Some(Vec::new()),
+            Predicate::Or(preds) => preds
+                .iter()
+                .map(Self::elements)
+                .collect::<Option<Vec<_>>>()
+                .map(|values| values.concat()),
+            _ => None,
+        }
+    }
+
+    /// Returns ids of records matching filter in ascending order, following the plan.
+    fn candidates(&self, plan: Plan, filter: &Filter) -> Vec<usize> {
+        let cond = filter.condition();
+        let ids = self.ids(plan, cond.narrow(self.schema.sig().len()));
+        let exact = cond.exact() && matches!(ids, Ids::Sorted(_));
+
+        ids.filter(|id| exact || cond.matches(self.records[*id].as_ref().unwrap().args()))
+            .collect()
+    }
+
+    /// Returns ids of records that may satisfy predicates of the plan in ascending order, the full condition is not checked.
+    /// - Ids from the index are merged as they are read.
+    /// - Ids from the record tree are collected first if the first column is looked up by value.
+    /// - Otherwise every record is visited in order, so the caller checks the condition.
+    fn ids(&self, plan: Plan, preds: Vec<Predicate>) -> Ids<'_> {
+        match plan {
+            Plan::Tree { .. } if preds.first().and_then(Self::values).is_some() => {
+                let mut ids = Walk::new(&self.tree, preds).collect::<Vec<_>>();
+                ids.sort_unstable();
+                Ids::Sorted(ids.into_iter())
+            }
+            Plan::Tree { .. } => Ids::Scan(self.records.iter().enumerate()),
+            Plan::Index { column, .. } => {
+                let index = &self.indexes[&column];
+                let mut values = self
+                    .index_values(column, &preds[column])
+                    .unwrap_or_default();
+                values.sort_unstable();
+                values.dedup();
+
+                Ids::Index(Merge {
+                    lists: values
+                        .into_iter()
+                        .filter_map(|value| index.get(&value))
+                        .map(|ids| ids.iter().peekable())
+                        .collect(),
+                    last: None,
+                })
+            }
+        }
+    }
+
+    /// Returns a lazy iterator over records matching filter.
+    /// - Records are found as the iterator advances, so `next`, `any` and `take` stop early.
+    /// - Records looked up by value in the first column are found together, as few records share a key.
+    /// - Records come in the order they are declared as in `find`, unless the filter sorts results.
+    ///
+    /// Sorted results are found all at once before the first record is returned.
+    pub fn iter_matches(&self, filter: Filter) -> Result<Matches<'_>, Error> {
+        let filter = self.prepare(filter)?;
+        let cond = filter.condition();
+        let preds = cond.narrow(self.schema.sig().len());
+        let plan = self.plan(&preds);
+
+        if !filter.sort().is_empty() {
+            let ids = self.page(self.candidates(plan, &filter), &filter);
+            return Ok(Matches {
+                set: self,
+                ids: Ids::Sorted(ids.into_iter()),
+                cond: None,
+                skip: 0,
+                take: None,
             });
         }
 
-        Ok(self.tree.find(filter.args()))
+        let ids = self.ids(plan, preds);
+        let exact = cond.exact() && matches!(ids, Ids::Sorted(_));
+        Ok(Matches {
+            set: self,
+            ids,
+            cond: (!exact).then(|| cond.clone()),
+            skip: filter.offset(),
+            take: filter.limit(),
+        })
+    }
+
+    /// Returns number of records matching filter, without collecting them.
+    pub fn count(&self, filter: Filter) -> Result<usize, Error> {
+        Ok(self.iter_matches(filter)?.count())
+    }
+
+    /// Computes aggregates over records matching filter, in the order of `aggregates`.
+    pub fn aggregate(
+        &self,
+        filter: Filter,
+        aggregates: &[Aggregate],
+    ) -> Result<Vec<Option<Field>>, Error> {
+        let mut aggregator = Aggregator::new(&self.schema, aggregates)?;
+        for record in self.iter_matches(filter)? {
+            aggregator.push(record);
+        }
+        aggregator.finish()
+    }
+
+    /// Returns values of columns for each record matching filter, in the order of `find`.
+    pub fn project(&self, filter: Filter, columns: &[usize]) -> Result<Vec<Vec<Field>>, Error> {
+        for column in columns {
+            self.column(*column)?;
+        }
+
+        Ok(self
+            .find(filter)?
+            .into_iter()
+            .map(|record| project(record, columns))
+            .collect())
+    }
+
+    /// Returns distinct values of columns among records matching filter, in ascending order.
+    /// - Sorting in the filter is ignored, offset and limit apply to the distinct values.
+    /// - Leading columns are read from the record tree without visiting records.
+    pub fn distinct(&self, filter: Filter, columns: &[usize]) -> Result<Vec<Vec<Field>>, Error> {
+        for column in columns {
+            self.column(*column)?;
+        }
+
+        let filter = self.prepare(filter)?;
+        let cond = filter.condition();
+        let preds = cond.narrow(self.schema.sig().len());
+
+        let mut values = BTreeSet::new();
+        if !columns.is_empty()
+            && columns.iter().enumerate().all(|(i, column)| i == *column)
+            && matches!(self.plan(&preds), Plan::Tree { .. })
+            && cond.exact()
+            && preds[columns.len()..]
+                .iter()
+                .all(|pred| matches!(pred, Predicate::Any))
+        {
+            let mut keys = BTreeSet::new();
+            self.tree
+                .prefixes(&preds[..columns.len()], &mut Vec::new(), &mut keys);
+            // keys in the tree are upcast
+            values.extend(keys.into_iter().map(|key| {
+                key.iter()
+                    .zip(self.schema.sig())
+                    .map(|(field, sig)| sig.cast(field).unwrap_or_else(|| field.clone()))
+                    .collect::<Vec<_>>()
+            }));
+        } else {
+            let all = Filter::with_condition(filter.record().to_string(), cond.clone());
+            for record in self.iter_matches(all)? {
+                values.insert(project(record, columns));
+            }
+        }
+
+        Ok(values
+            .into_iter()
+            .skip(filter.offset())
+            .take(filter.limit().unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    /// Pairs records matching filter with records of another type matching `other_filter`,
+    /// where the value of `column` equals the value of `other_column`.
+    /// - Pairs follow the order of `find` on this set, then the declaration order of the other set.
+    /// - Sorting, offset and limit only apply to this set.
+    /// - Null and values that cannot be converted to the type of `other_column` match nothing.
+    ///
+    /// The other set is looked up record by record if `other_column` is indexed or first,
+    /// otherwise its matches are hashed once.
+    pub fn join<'a>(
+        &'a self,
+        filter: Filter,
+        column: usize,
+        other: &'a RecordSet,
+        other_filter: Filter,
+        other_column: usize,
+    ) -> Result<Vec<(&'a Record, &'a Record)>, Error> {
+        self.column(column)?;
+        other.column(other_column)?;
+
+        let other_filter = other.prepare(other_filter)?;
+        let other_cond = other_filter.condition().clone();
+        let other_sig = &other.schema.sig()[other_column];
+        let found = self.find(filter)?;
+        let mut pairs = Vec::new();
+
+        if other_column == 0 || other.indexes.contains_key(&other_column) {
+            for record in found {
+                let Some(value) = other_sig
+                    .cast(&record.args()[column])
+                    .filter(|value| !value.is_null())
+                else {
+                    continue;
+                };
+                let probe = Filter::with_condition(
+                    other_filter.record().to_string(),
+                    other_cond
+                        .clone()
+                        .and(Condition::field(other_column, Predicate::Eq(value))),
+                );
+                pairs.extend(other.find(probe)?.into_iter().map(|found| (record, found)));
+            }
+        } else {
+            let mut table: HashMap<Field, Vec<&Record>> = HashMap::new();
+            for found in other.find(Filter::with_condition(
+                other_filter.record().to_string(),
+                other_cond,
+            ))? {
+                table
+                    .entry(found.args()[other_column].upcast())
+                    .or_default()
+                    .push(found);
+            }
+
+            for record in found {
+                let Some(value) = other_sig
+                    .cast(&record.args()[column])
+                    .filter(|value| !value.is_null())
+                else {
+                    continue;
+                };
+                if let Some(matches) = table.get(&value.upcast()) {
+                    pairs.extend(matches.iter().map(|found| (record, *found)));
+                }
+            }
+        }
+
+        Ok(pairs)
+    }
+
+    /// Computes aggregates over records matching filter, for each distinct value of columns.
+    pub fn group_by(
+        &self,
+        filter: Filter,
+        columns: &[usize],
+        aggregates: &[Aggregate],
+    ) -> Result<Vec<Group>, Error> {
+        Aggregator::new(&self.schema, aggregates)?.group(
+            &self.schema,
+            columns,
+            self.iter_matches(filter)?,
+        )
+    }
+}
+
+/// Lazy iterator over records matching a filter, see `RecordSet::iter_matches`.
+pub struct Matches<'a> {
+    set: &'a RecordSet,
+    ids: Ids<'a>,
+    // checked against each record unless the ids only contain matches
+    cond: Option<Condition>,
+    skip: usize,
+    take: Option<usize>,
+}
+
+impl<'a> Iterator for Matches<'a> {
+    type Item = &'a Record;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.take == Some(0) {
+                return None;
+            }
+
+            let record = self.set.records[self.ids.next()?].as_ref().unwrap();
+            if let Some(cond) = &self.cond {
+                if !cond.matches(record.args()) {
+                    continue;
+                }
+            }
+            if self.skip > 0 {
+                self.skip -= 1;
+                continue;
+            }
+            if let Some(take) = &mut self.take {
+                *take -= 1;
+            }
+
+            return Some(record);
+        }
+    }
+
+    /// Upper bound is the number of records not yet visited.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let upper = self.ids.size_hint().1;
+        match self.take {
+            Some(take) => (0, Some(upper.map_or(take, |upper| upper.min(take)))),
+            None => (0, upper),
+        }
+    }
+}
+
+/// Ids of records to visit, in ascending order.
+enum Ids<'a> {
+    Index(Merge<'a>),
+    Sorted(vec::IntoIter<usize>),
+    // every record in declaration order, removed records are skipped
+    Scan(Enumerate<slice::Iter<'a, Option<Record>>>),
+}
+
+impl Iterator for Ids<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Index(ids) => ids.next(),
+            Self::Sorted(ids) => ids.next(),
+            Self::Scan(records) => records
+                .find(|(_, record)| record.is_some())
+                .map(|(id, _)| id),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Self::Index(_) => (0, None),
+            Self::Sorted(ids) => ids.size_hint(),
+            Self::Scan(records) => (0, records.size_hint().1),
+        }
+    }
+}
+
+/// Merges ascending lists of ids into one ascending list, ids in more than one list are yielded once.
+struct Merge<'a> {
+    lists: Vec<Peekable<btree_set::Iter<'a, usize>>>,
+    last: Option<usize>,
+}
+
+impl Iterator for Merge<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (list, id) = self
+                .lists
+                .iter_mut()
+                .enumerate()
+                .filter_map(|(i, ids)| Some((i, **ids.peek()?)))
+                .min_by_key(|(_, id)| *id)?;
+            self.lists[list].next();
+
+            if self.last != Some(id) {
+                self.last = Some(id);
+                return Some(id);
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// Represents how records matching a filter are found.
+pub enum Plan {
+    /// Walk the record tree in column order, skipping branches that cannot match.
+    Tree { estimate: usize },
+    /// Look up values in the secondary index of a column.
+    Index { column: usize, estimate: usize },
+}
+
+impl Plan {
+    /// Returns the estimated number of records visited.
+    pub fn estimate(&self) -> usize {
+        match self {
+            Self::Tree { estimate } | Self::Index { estimate, .. } => *estimate,
+        }
+    }
+}
+
+impl Display for Plan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tree { estimate } => {
+                f.write_fmt(format_args!("tree walk (about {estimate} records)"))
+            }
+            Self::Index { column, estimate } => f.write_fmt(format_args!(
+                "index on column {column} ({estimate} records)"
+            )),
+        }
     }
 }
 
 #[derive(Debug)]
 enum RecordTree {
     Index(HashMap<Field, RecordTree>),
-    Unit(Record),
+    Ordered(BTreeMap<Field, RecordTree>),
+    // networks by prefix, keys are kept to be yielded with the sub-trees, null is kept beside the trie
+    Prefix(PrefixTrie<(Field, RecordTree)>, Option<Box<RecordTree>>),
+    Unit(usize),
     Void,
 }
 
 impl RecordTree {
-    pub fn put(&mut self, args: &[Field], mut record: Record) -> Option<Record> {
+    /// Creates an empty index for a column of the type.
+    fn branch(sig: &Signature) -> Self {
+        if *sig == Signature::Cidr {
+            Self::Prefix(PrefixTrie::new(), None)
+        } else if sig.ordered() {
+            Self::Ordered(BTreeMap::new())
+        } else {
+            Self::Index(HashMap::new())
+        }
+    }
+
+    fn get_mut(&mut self, key: &Field) -> Option<&mut Self> {
+        match self {
+            Self::Index(map) => map.get_mut(key),
+            Self::Ordered(map) => map.get_mut(key),
+            Self::Prefix(_, null) if key.is_null() => null.as_deref_mut(),
+            Self::Prefix(trie, _) => {
+                let (addr, len) = key.as_cidr()?;
+                trie.get_mut(addr, len).map(|(_, tree)| tree)
+            }
+            _ => None,
+        }
+    }
+
+    fn insert(&mut self, key: Field, tree: Self) {
         match self {
             Self::Index(map) => {
-                let upcast = args[0].upcast();
-                if let Some(entry) = map.get_mut(&upcast) {
-                    entry.put(&args[1..], record)
-                } else if args.len() == 1 {
-                    map.insert(upcast, Self::Unit(record)).map(|u| match u {
-                        Self::Unit(u) => u,
-                        _ => unreachable!("wrong length"),
-                    })
-                } else {
-                    let mut index = Self::Index(HashMap::new());
-                    index.put(&args[1..], record);
-                    map.insert(upcast, index);
-                    None
-                }
+                map.insert(key, tree);
             }
-            Self::Unit(r) => {
-                std::mem::swap(r, &mut record);
-                Some(record)
+            Self::Ordered(map) => {
+                map.insert(key, tree);
             }
-            Self::Void => unreachable!("wrong length"),
+            Self::Prefix(_, null) if key.is_null() => *null = Some(Box::new(tree)),
+            Self::Prefix(trie, _) => {
+                let (addr, len) = key.as_cidr().expect("wrong type");
+                trie.insert(addr, len, (key, tree));
+            }
+            _ => unreachable!("wrong length"),
         }
     }
 
-    pub fn find_one(&self, filter: &[Option<Field>]) -> Option<&Record> {
+    fn take(&mut self, key: &Field) -> Option<Self> {
         match self {
-            Self::Void => None,
-            Self::Unit(r) => Some(r),
-            Self::Index(map) => {
-                if let Some(field) = &filter[0] {
-                    match map.get(field) {
-                        Some(t) => t.find_one(&filter[1..]),
-                        None => None,
-                    }
-                } else {
-                    for t in map.values() {
-                        let res = t.find_one(&filter[1..]);
-                        if res.is_some() {
-                            return res;
-                        }
-                    }
+            Self::Index(map) => map.remove(key),
+            Self::Ordered(map) => map.remove(key),
+            Self::Prefix(_, null) if key.is_null() => null.take().map(|tree| *tree),
+            Self::Prefix(trie, _) => {
+                let (addr, len) = key.as_cidr()?;
+                trie.remove(addr, len).map(|(_, tree)| tree)
+            }
+            _ => None,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Self::Index(map) => map.is_empty(),
+            Self::Ordered(map) => map.is_empty(),
+            Self::Prefix(trie, null) => trie.len() == 0 && null.is_none(),
+            Self::Unit(_) => false,
+            Self::Void => true,
+        }
+    }
+
+    /// Returns sub-trees with keys satisfying the predicate.
+    fn children(&self, pred: &Predicate) -> Children<'_> {
+        match (self, pred) {
+            (Self::Index(map), Predicate::Eq(field)) => Children::One(map.get_key_value(field)),
+            (Self::Ordered(map), Predicate::Eq(field)) => Children::One(map.get_key_value(field)),
+            (Self::Prefix(_, null), Predicate::Eq(Field::Null) | Predicate::IsNull) => {
+                Children::One(null.as_deref().map(|tree| (&NULL, tree)))
+            }
+            (Self::Prefix(trie, _), Predicate::Eq(Field::Cidr(addr, len))) => {
+                Children::One(trie.get(*addr, *len).map(|(key, tree)| (key, tree)))
+            }
+            (Self::Prefix(trie, _), Predicate::Contains(Field::Cidr(addr, len))) => {
+                Children::Prefix(
+                    trie.covering(*addr, *len)
+                        .into_iter()
+                        .map(|(key, tree)| (key, tree))
+                        .collect::<Vec<_>>()
+                        .into_iter(),
+                )
+            }
+            (Self::Prefix(trie, null), _) => Children::Prefix(
+                trie.values()
+                    .into_iter()
+                    .map(|(key, tree)| (key, tree))
+                    .chain(null.as_deref().map(|tree| (&NULL, tree)))
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            ),
+            (Self::Index(map), _) => Children::Index(map.iter()),
+            (Self::Ordered(map), _) => match pred.bounds() {
+                Some((start, end)) => Children::Ordered(map.range((start.cloned(), end.cloned()))),
+                None => Children::One(None),
+            },
+            _ => Children::One(None),
+        }
+    }
 
+    /// Collects keys of the first columns, one for each predicate, of branches satisfying them.
+    fn prefixes(&self, preds: &[Predicate], key: &mut Vec<Field>, out: &mut BTreeSet<Vec<Field>>) {
+        let Some((pred, rest)) = preds.split_first() else {
+            out.insert(key.clone());
+            return;
+        };
+
+        let mut children = self.children(pred);
+        while let Some((field, tree)) = children.next(pred) {
+            key.push(field.clone());
+            tree.prefixes(rest, key, out);
+            key.pop();
+        }
+    }
+
+    /// Collects ids of records with keys equal to, or patterns matching, the value of each column.
+    /// - Patterns are ordered after other keys, so only the last keys of ordered columns are checked.
+    fn rules(&self, values: &[Field], out: &mut Vec<usize>) {
+        let Some((value, rest)) = values.split_first() else {
+            self.all(out);
+            return;
+        };
+
+        let is_pattern = |key: &Field| matches!(key, Field::Pattern(_));
+        let branches: Vec<(&Field, &RecordTree)> = match self {
+            Self::Index(map) => map
+                .iter()
+                .filter(|(key, _)| *key == value || is_pattern(key))
+                .collect(),
+            Self::Ordered(map) => map
+                .get_key_value(value)
+                .into_iter()
+                .chain(map.iter().rev().take_while(|(key, _)| is_pattern(key)))
+                .collect(),
+            Self::Prefix(trie, null) => match value {
+                Field::Null => null
+                    .as_deref()
+                    .map(|tree| (&NULL, tree))
+                    .into_iter()
+                    .collect(),
+                Field::Cidr(addr, len) => trie
+                    .covering(*addr, *len)
+                    .into_iter()
+                    .map(|(key, tree)| (key, tree))
+                    .collect(),
+                _ => Vec::new(),
+            },
+            Self::Unit(_) | Self::Void => Vec::new(),
+        };
+
+        for (key, tree) in branches {
+            if key
+                .as_pattern()
+                .is_none_or(|pattern| pattern.matches(value))
+            {
+                tree.rules(rest, out);
+            }
+        }
+    }
+
+    /// Collects ids of all records in the tree.
+    fn all(&self, out: &mut Vec<usize>) {
+        match self {
+            Self::Index(map) => map.values().for_each(|tree| tree.all(out)),
+            Self::Ordered(map) => map.values().for_each(|tree| tree.all(out)),
+            Self::Prefix(trie, null) => {
+                trie.values()
+                    .into_iter()
+                    .for_each(|(_, tree)| tree.all(out));
+                null.iter().for_each(|tree| tree.all(out));
+            }
+            Self::Unit(id) => out.push(*id),
+            Self::Void => {}
+        }
+    }
+
+    /// Returns number of keys in the first column.
+    fn keys(&self) -> usize {
+        match self {
+            Self::Index(map) => map.len(),
+            Self::Ordered(map) => map.len(),
+            Self::Prefix(trie, null) => trie.len() + null.is_some() as usize,
+            Self::Unit(_) => 1,
+            Self::Void => 0,
+        }
+    }
+
+    pub fn put(&mut self, args: &[Field], sig: &[Signature], mut id: usize) -> Option<usize> {
+        match self {
+            Self::Unit(r) => {
+                std::mem::swap(r, &mut id);
+                Some(id)
+            }
+            Self::Void => unreachable!("wrong length"),
+            _ => {
+                let key = args[0].upcast();
+                if let Some(entry) = self.get_mut(&key) {
+                    entry.put(&args[1..], &sig[1..], id)
+                } else if args.len() == 1 {
+                    self.insert(key, Self::Unit(id));
+                    None
+                } else {
+                    let mut branch = Self::branch(&sig[1]);
+                    branch.put(&args[1..], &sig[1..], id);
+                    self.insert(key, branch);
                     None
                 }
             }
         }
     }
 
-    pub fn find(&self, filter: &[Option<Field>]) -> Vec<&Record> {
+    pub fn remove(&mut self, args: &[Field]) -> Option<usize> {
+        let key = args[0].upcast();
+        let entry = self.get_mut(&key)?;
+        let removed = match entry {
+            Self::Unit(_) => None,
+            _ => entry.remove(&args[1..]),
+        };
+
+        if !matches!(entry, Self::Unit(_)) && !entry.is_empty() {
+            return removed;
+        }
+
+        match self.take(&key) {
+            Some(Self::Unit(id)) => Some(id),
+            _ => removed,
+        }
+    }
+}
+
+/// Cursor over sub-trees and their keys, the predicate is checked by `next`.
+enum Children<'a> {
+    One(Option<(&'a Field, &'a RecordTree)>),
+    Index(hash_map::Iter<'a, Field, RecordTree>),
+    Ordered(btree_map::Range<'a, Field, RecordTree>),
+    Prefix(vec::IntoIter<(&'a Field, &'a RecordTree)>),
+}
+
+impl<'a> Children<'a> {
+    fn next(&mut self, pred: &Predicate) -> Option<(&'a Field, &'a RecordTree)> {
         match self {
-            Self::Void => Vec::new(),
-            Self::Unit(r) => vec![r],
-            Self::Index(map) => {
-                if let Some(field) = &filter[0] {
-                    match map.get(field) {
-                        Some(t) => t.find(&filter[1..]),
-                        None => Vec::new(),
-                    }
-                } else {
-                    let mut res = Vec::new();
-                    for t in map.values() {
-                        res.append(&mut t.find(&filter[1..]));
-                    }
+            Self::One(tree) => tree.take(),
+            Self::Index(iter) => iter.find(|(key, _)| pred.matches(key)),
+            Self::Ordered(iter) => iter.find(|(key, _)| pred.matches(key)),
+            Self::Prefix(iter) => iter.find(|(key, _)| pred.matches(key)),
+        }
+    }
+}
+
+/// Walks the record tree depth first, yielding ids of records satisfying a predicate for each column.
+struct Walk<'a> {
+    preds: Vec<Predicate>,
+    root: Option<&'a RecordTree>,
+    stack: Vec<Children<'a>>,
+}
+
+impl<'a> Walk<'a> {
+    fn new(tree: &'a RecordTree, preds: Vec<Predicate>) -> Self {
+        Self {
+            stack: Vec::with_capacity(preds.len()),
+            preds,
+            root: Some(tree),
+        }
+    }
+}
 
-                    res
+impl Iterator for Walk<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.root.take() {
+            Some(RecordTree::Unit(id)) => return Some(*id),
+            Some(RecordTree::Void) => return None,
+            Some(tree) => self.stack.push(tree.children(&self.preds[0])),
+            None => {}
+        }
+
+        while !self.stack.is_empty() {
+            let depth = self.stack.len() - 1;
+            match self.stack[depth]
+                .next(&self.preds[depth])
+                .map(|(_, tree)| tree)
+            {
+                Some(RecordTree::Unit(id)) => return Some(*id),
+                Some(RecordTree::Void) => {}
+                Some(tree) => {
+                    let children = tree.children(&self.preds[depth + 1]);
+                    self.stack.push(children);
+                }
+                None => {
+                    self.stack.pop();
                 }
             }
         }
+
+        None
     }
 }
+
+/// Returns keys a field is indexed by, each element of a list.
+fn index_keys(field: &Field) -> Vec<Field> {
+    match field.upcast() {
+        Field::List(items) => items,
+        key => vec![key],
+    }
+}
+
+/// Returns values of columns in a record.
+fn project(record: &Record, columns: &[usize]) -> Vec<Field> {
+    columns
+        .iter()
+        .map(|column| record.args()[*column].clone())
+        .collect()
+}
@@ -2,9 +2,13 @@ use std::{
     collections::{hash_map::Entry, HashMap},
     fmt::Display,
     str::FromStr,
+    sync::Arc,
 };
 
-use crate::{Error, Field, Filter, ParseError, RecordSet, Signature};
+use crate::{
+    Condition, Constraint, EnumDef, Error, Field, Filter, ParseError, Pattern, Predicate,
+    RecordSet, Signature,
+};
 
 /// Represents a single type definition.
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -13,6 +17,120 @@ pub struct SchemaOne {
     location: String,
     line: u32,
     sig: Vec<Signature>,
+    columns: Vec<ColumnDef>,
+}
+
+/// Represents options of a column in a type definition.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ColumnDef {
+    name: Option<String>,
+    nullable: bool,
+    default: Option<Field>,
+    constraints: Vec<Constraint>,
+    validity: Option<Validity>,
+    pattern: bool,
+    indexed: bool,
+}
+
+/// Marks a column as a bound of the time a record is valid, declared after the type.
+/// - Bounds are date, datetime or integer columns, integers are seconds since epoch.
+/// - Null is no bound.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Validity {
+    /// Valid from the value on, declared with `not_before`.
+    NotBefore,
+    /// Valid until and including the value, declared with `not_after`.
+    NotAfter,
+    /// Valid until the value, declared with `expires`.
+    Expires,
+}
+
+impl Display for Validity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::NotBefore => "not_before",
+            Self::NotAfter => "not_after",
+            Self::Expires => "expires",
+        })
+    }
+}
+
+impl Validity {
+    /// Returns a predicate on the bound that matches if a record is valid at the instant.
+    fn valid_at(&self, instant: Field) -> Predicate {
+        match self {
+            Self::NotBefore => Predicate::Le(instant),
+            Self::NotAfter => Predicate::Ge(instant),
+            Self::Expires => Predicate::Gt(instant),
+        }
+    }
+
+    /// Returns a predicate on the bound that matches if a record is no longer valid at the instant.
+    fn expired_at(&self, instant: Field) -> Predicate {
+        match self {
+            Self::NotBefore => Predicate::None,
+            Self::NotAfter => Predicate::Lt(instant),
+            Self::Expires => Predicate::Le(instant),
+        }
+    }
+
+    /// Convert an instant to the type of a bound, dates are days in UTC.
+    fn instant(sig: &Signature, instant: &Field) -> Field {
+        let converted = match (sig, instant) {
+            (Signature::Date, Field::DateTime { secs, .. }) => {
+                Some(Field::Date(secs.div_euclid(86400)))
+            }
+            (sig, Field::Date(_) | Field::DateTime { .. }) if sig.integer() => instant
+                .as_timestamp()
+                .and_then(|secs| sig.cast(&Field::I64(secs))),
+            _ => sig.cast(instant),
+        };
+        converted.unwrap_or_else(|| instant.clone())
+    }
+}
+
+impl ColumnDef {
+    /// Returns the column name, declared as `name:type`.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns true if the column accepts null, declared with `?` after the type.
+    pub fn nullable(&self) -> bool {
+        self.nullable
+    }
+
+    /// Returns the value used when the column is omitted, declared as `type=value`.
+    pub fn default(&self) -> Option<&Field> {
+        self.default.as_ref()
+    }
+
+    /// Returns rules that values of the column must follow.
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    /// Returns the first constraint the field does not satisfy.
+    pub fn violated(&self, field: &Field) -> Option<&Constraint> {
+        self.constraints
+            .iter()
+            .find(|constraint| !constraint.check(field))
+    }
+
+    /// Returns the bound of validity the column is, if any.
+    pub fn validity(&self) -> Option<Validity> {
+        self.validity
+    }
+
+    /// Returns true if values of the column can be patterns, declared with `pattern` after the type.
+    pub fn pattern(&self) -> bool {
+        self.pattern
+    }
+
+    /// Returns true if the column has a secondary index, declared with `indexed` after the type.
+    pub fn indexed(&self) -> bool {
+        self.indexed
+    }
 }
 
 impl Display for SchemaOne {
@@ -25,11 +143,7 @@ impl Display for SchemaOne {
             if self.sig.is_empty() {
                 "unit".to_string()
             } else {
-                self.sig
-                    .iter()
-                    .map(Signature::to_string)
-                    .collect::<Vec<_>>()
-                    .join(" ")
+                self.column_strings().join(" ")
             }
         ))
     }
@@ -37,8 +151,24 @@ impl Display for SchemaOne {
 
 impl SchemaOne {
     /// Parse a record into typed record.
+    /// - Trailing columns with default values can be omitted.
     pub fn parse(&self, args: Vec<&str>, location: &str, line: u32) -> Result<Vec<Field>, Error> {
-        if args.len() != self.sig.len() {
+        self.parse_quoted(
+            args.into_iter().map(|arg| (arg, false)).collect(),
+            location,
+            line,
+        )
+    }
+
+    /// Parse a record into typed record, with whether each argument is quoted.
+    /// - Quoted `"null"` is a string rather than null.
+    pub fn parse_quoted(
+        &self,
+        args: Vec<(&str, bool)>,
+        location: &str,
+        line: u32,
+    ) -> Result<Vec<Field>, Error> {
+        if args.len() > self.sig.len() || !self.defaults_from(args.len()) {
             return Err(Error::ParseError {
                 location: location.to_string(),
                 line,
@@ -51,10 +181,39 @@ impl SchemaOne {
 
         let mut out = Vec::with_capacity(args.len());
 
-        for (arg, schem) in args.into_iter().zip(self.sig.iter()) {
-            out.push(schem.parse(arg, location, line)?);
+        for (((arg, quoted), schem), column) in
+            args.into_iter().zip(self.sig.iter()).zip(&self.columns)
+        {
+            let field = match arg {
+                "null" if column.nullable && !quoted => Field::Null,
+                _ if column.pattern => match Pattern::parse(schem, arg, location, line)? {
+                    Some(pattern) => Field::from(pattern),
+                    None => schem.parse(arg, location, line)?,
+                },
+                _ => schem.parse(arg, location, line)?,
+            };
+
+            if let Some(constraint) = column.violated(&field) {
+                return Err(Error::ParseError {
+                    location: location.to_string(),
+                    line,
+                    reason: ParseError::ConstraintViolation {
+                        column: out.len(),
+                        constraint: Box::new(constraint.clone()),
+                        value: field.to_rules_string(),
+                    },
+                });
+            }
+
+            out.push(field);
         }
 
+        out.extend(
+            self.columns[out.len()..]
+                .iter()
+                .flat_map(|column| column.default.clone()),
+        );
+
         Ok(out)
     }
 
@@ -77,6 +236,136 @@ impl SchemaOne {
     pub fn sig(&self) -> &[Signature] {
         &self.sig
     }
+
+    /// Returns options of each column.
+    pub fn columns(&self) -> &[ColumnDef] {
+        &self.columns
+    }
+
+    /// Returns index of the column with the name.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|column| column.name() == Some(name))
+    }
+
+    /// Converts definition to how it is written in a rules file.
+    pub fn to_rules_string(&self) -> String {
+        std::iter::once(format!("{}:", self.label))
+            .chain(self.column_strings())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Returns a condition matching records valid at the instant, as bounded by columns marked with `Validity`.
+    /// - The instant is a date, datetime or seconds since epoch.
+    pub fn valid_at(&self, instant: &Field) -> Condition {
+        self.bounds()
+            .map(|(i, validity)| {
+                let pred = validity.valid_at(Validity::instant(&self.sig[i], instant));
+                match self.columns[i].nullable {
+                    true => Condition::field(i, pred.or(Predicate::IsNull)),
+                    false => Condition::field(i, pred),
+                }
+            })
+            .fold(Condition::And(Vec::new()), Condition::and)
+    }
+
+    /// Returns a condition matching records past any of their `not_after` or `expires` bounds at the instant.
+    pub fn expired_at(&self, instant: &Field) -> Condition {
+        self.bounds()
+            .filter(|(_, validity)| *validity != Validity::NotBefore)
+            .map(|(i, validity)| {
+                Condition::field(
+                    i,
+                    validity.expired_at(Validity::instant(&self.sig[i], instant)),
+                )
+            })
+            .fold(Condition::Or(Vec::new()), Condition::or)
+    }
+
+    /// Returns columns marked as bounds of validity.
+    fn bounds(&self) -> impl Iterator<Item = (usize, Validity)> + '_ {
+        self.columns
+            .iter()
+            .enumerate()
+            .filter_map(|(i, column)| Some((i, column.validity?)))
+    }
+
+    /// Mark a column as having a secondary index.
+    pub(crate) fn set_indexed(&mut self, column: usize, indexed: bool) {
+        self.columns[column].indexed = indexed;
+    }
+
+    /// Returns each column as written in a definition.
+    pub(crate) fn column_strings(&self) -> Vec<String> {
+        self.sig
+            .iter()
+            .zip(self.columns.iter())
+            .map(|(sig, column)| {
+                let mut sig = match (&column.name, column.nullable) {
+                    (Some(name), true) => format!("{name}:{sig}?"),
+                    (Some(name), false) => format!("{name}:{sig}"),
+                    (None, true) => format!("{sig}?"),
+                    (None, false) => sig.to_string(),
+                };
+                if let Some(default) = &column.default {
+                    sig = format!("{sig}={}", default.to_rules_string());
+                }
+                for constraint in &column.constraints {
+                    sig = format!("{sig} {constraint}");
+                }
+                if let Some(validity) = column.validity {
+                    sig = format!("{sig} {validity}");
+                }
+                if column.pattern {
+                    sig = format!("{sig} pattern");
+                }
+                match column.indexed {
+                    true => format!("{sig} indexed"),
+                    false => sig,
+                }
+            })
+            .collect()
+    }
+
+    /// Convert fields to the types of this definition, returns `None` if any field does not fit.
+    /// - Null only fits nullable columns.
+    /// - Trailing columns with default values can be omitted.
+    pub fn cast(&self, fields: &[Field]) -> Option<Vec<Field>> {
+        if fields.len() > self.sig.len() || !self.defaults_from(fields.len()) {
+            return None;
+        }
+
+        fields
+            .iter()
+            .enumerate()
+            .map(|(column, field)| self.cast_one(column, field))
+            .chain(
+                self.columns[fields.len()..]
+                    .iter()
+                    .map(|column| column.default.clone()),
+            )
+            .collect()
+    }
+
+    /// Returns true if every column from the index has a default value.
+    fn defaults_from(&self, column: usize) -> bool {
+        self.columns[column..]
+            .iter()
+            .all(|column| column.default.is_some())
+    }
+
+    /// Convert a field to the type of a column, returns `None` if it does not fit.
+    pub(crate) fn cast_one(&self, column: usize, field: &Field) -> Option<Field> {
+        match field {
+            Field::Null if !self.columns[column].nullable => None,
+            Field::Pattern(_) if !self.columns[column].pattern => None,
+            _ => self.sig[column]
+                .cast(field)
+                .filter(|field| self.columns[column].violated(field).is_none()),
+        }
+    }
 }
 
 impl SchemaOne {
@@ -85,13 +374,148 @@ impl SchemaOne {
         location: String,
         line: u32,
         sig: &[String],
+        enums: &HashMap<String, Arc<EnumDef>>,
     ) -> Result<Self, ParseError> {
         let args = sig.iter().filter(|s| !s.is_empty()).collect::<Vec<_>>();
 
         let mut sig = Vec::with_capacity(args.len());
+        let mut columns: Vec<ColumnDef> = Vec::with_capacity(args.len());
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match (arg.as_str(), columns.last_mut(), sig.last()) {
+                ("indexed", Some(column), _) => column.indexed = true,
+                ("pattern", Some(column), Some(ty)) => {
+                    if matches!(ty, Signature::Cidr | Signature::List(_)) {
+                        return Err(ParseError::IllegalConstraint {
+                            got: format!("{ty} pattern"),
+                        });
+                    }
+                    column.pattern = true;
+                }
+                ("not_before" | "not_after" | "expires", Some(column), Some(ty)) => {
+                    if !matches!(ty, Signature::Date | Signature::DateTime) && !ty.integer() {
+                        return Err(ParseError::IllegalConstraint {
+                            got: format!("{ty} {arg}"),
+                        });
+                    }
+
+                    column.validity = Some(match arg.as_str() {
+                        "not_before" => Validity::NotBefore,
+                        "not_after" => Validity::NotAfter,
+                        _ => Validity::Expires,
+                    });
+                }
+                ("in", Some(column), Some(ty)) => {
+                    let mut set = String::new();
+                    for arg in args.by_ref() {
+                        set.push_str(arg);
+                        set.push(' ');
+                        if arg.ends_with('}') {
+                            break;
+                        }
+                    }
+
+                    let values = set
+                        .trim()
+                        .strip_prefix('{')
+                        .and_then(|set| set.strip_suffix('}'))
+                        .ok_or(ParseError::IllegalConstraint {
+                            got: format!("in {}", set.trim()),
+                        })?
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|value| !value.is_empty())
+                        .map(|value| Self::parse_value(ty, value, &location, line))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    column.constraints.push(Constraint::In(values));
+                }
+                ("len" | "glob", Some(column), Some(ty)) => {
+                    let value = args.next().ok_or(ParseError::MissingArguments)?;
+                    if !matches!(ty, Signature::IStr | Signature::UStr) {
+                        return Err(ParseError::IllegalConstraint {
+                            got: format!("{arg} {value}"),
+                        });
+                    }
+
+                    column.constraints.push(match arg.as_str() {
+                        "len" => {
+                            let (start, end) = Constraint::len(value)?;
+                            Constraint::Len(start, end)
+                        }
+                        _ => Constraint::Glob(value.to_string()),
+                    });
+                }
+                (range, Some(column), Some(ty)) if Constraint::is_range(range) => {
+                    let (start, end) = Constraint::range(range, ty, &location, line)?;
+                    column.constraints.push(Constraint::Range(start, end));
+                }
+                _ => {
+                    let (decl, default) = match arg.split_once('=') {
+                        Some((decl, default)) => (decl, Some(default)),
+                        None => (arg.as_str(), None),
+                    };
+                    let (name, ty) = match decl.split_once(':') {
+                        Some((name, ty)) => (Some(name), ty),
+                        None => (None, decl),
+                    };
+
+                    if let Some(name) = name {
+                        if name.is_empty() || name.chars().all(|c| c.is_ascii_digit()) {
+                            return Err(ParseError::IllegalColumnName {
+                                name: name.to_string(),
+                            });
+                        }
+                        if columns.iter().any(|column| column.name() == Some(name)) {
+                            return Err(ParseError::DuplicatedColumn {
+                                name: name.to_string(),
+                            });
+                        }
+                    }
+
+                    let (ty, nullable) = match ty.strip_suffix('?') {
+                        Some(ty) => (ty, true),
+                        None => (ty, false),
+                    };
+
+                    let ty = match ty.strip_prefix('[').and_then(|ty| ty.strip_suffix(']')) {
+                        Some(inner) => Signature::list(Self::resolve(inner, enums)?).ok_or(
+                            ParseError::UnrecognisedType {
+                                got: ty.to_string(),
+                            },
+                        )?,
+                        None => Self::resolve(ty, enums)?,
+                    };
+                    let default = match default {
+                        Some("null") if nullable => Some(Field::Null),
+                        Some(default) => Some(Self::parse_value(&ty, default, &location, line)?),
+                        None => None,
+                    };
+
+                    sig.push(ty);
+                    columns.push(ColumnDef {
+                        name: name.map(str::to_string),
+                        nullable,
+                        default,
+                        constraints: Vec::new(),
+                        validity: None,
+                        pattern: false,
+                        indexed: false,
+                    });
+                }
+            }
+        }
 
-        for arg in args {
-            sig.push(Signature::from_str(arg)?);
+        for (i, column) in columns.iter().enumerate() {
+            if let Some(default) = &column.default {
+                if let Some(constraint) = column.violated(default) {
+                    return Err(ParseError::ConstraintViolation {
+                        column: i,
+                        constraint: Box::new(constraint.clone()),
+                        value: default.to_rules_string(),
+                    });
+                }
+            }
         }
 
         Ok(Self {
@@ -99,17 +523,39 @@ impl SchemaOne {
             location,
             line,
             sig,
+            columns,
         })
     }
 
+    /// Looks up a type by name, enums first.
+    fn resolve(ty: &str, enums: &HashMap<String, Arc<EnumDef>>) -> Result<Signature, ParseError> {
+        match enums.get(ty) {
+            Some(def) => Ok(Signature::Enum(def.clone())),
+            None => Signature::from_str(ty),
+        }
+    }
+
+    /// Parse a value written in a definition.
+    pub(crate) fn parse_value(
+        sig: &Signature,
+        s: &str,
+        location: &str,
+        line: u32,
+    ) -> Result<Field, ParseError> {
+        match sig.parse(s, location, line) {
+            Ok(field) => Ok(field),
+            Err(Error::ParseError { reason, .. }) => Err(reason),
+            Err(_) => unreachable!("parsing only fails with parse errors"),
+        }
+    }
+
     /// Check if filter is in correct type.
     pub fn match_filter(&self, filter: &Filter) -> bool {
-        filter.args().len() == self.sig.len()
-            && !filter
-                .args()
+        filter.condition().fits(&self.sig)
+            && filter
+                .sort()
                 .iter()
-                .zip(self.sig.iter())
-                .any(|(f, s)| f.as_ref().is_some_and(|f| &f.sig() != s))
+                .all(|(column, _)| *column < self.sig.len())
     }
 }
 
@@ -119,12 +565,15 @@ pub struct Schema(HashMap<String, SchemaOne>);
 
 // (location, line, label, schema)
 impl Schema {
-    /// Parse schema stream into self.
-    pub fn parse(entries: Vec<(String, u32, String, Vec<String>)>) -> Result<Self, Error> {
+    /// Parse schema stream into self, column types may name any of the enums.
+    pub fn parse(
+        entries: Vec<(String, u32, String, Vec<String>)>,
+        enums: &HashMap<String, Arc<EnumDef>>,
+    ) -> Result<Self, Error> {
         let mut parsed_entries = HashMap::with_capacity(entries.len());
 
         for (location, line, label, sig) in entries.into_iter() {
-            if label.as_str() == "include" || label.contains(' ') {
+            if matches!(label.as_str(), "include" | "enum") || label.contains(' ') {
                 return Err(Error::ParseError {
                     location,
                     line,
@@ -132,16 +581,17 @@ impl Schema {
                 });
             }
 
-            let record = match SchemaOne::from_sig(label.clone(), location.clone(), line, &sig) {
-                Ok(sig) => sig,
-                Err(e) => {
-                    return Err(Error::ParseError {
-                        location: location.to_string(),
-                        line,
-                        reason: e,
-                    })
-                }
-            };
+            let record =
+                match SchemaOne::from_sig(label.clone(), location.clone(), line, &sig, enums) {
+                    Ok(sig) => sig,
+                    Err(e) => {
+                        return Err(Error::ParseError {
+                            location: location.to_string(),
+                            line,
+                            reason: e,
+                        })
+                    }
+                };
 
             match parsed_entries.entry(label) {
                 Entry::Vacant(en) => en.insert(record),
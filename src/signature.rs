@@ -1,8 +1,17 @@
-use std::{fmt::Display, str::FromStr};
+use std::{
+    fmt::Display,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+    sync::Arc,
+};
 
-use crate::{Error, Field, ParseError};
+use crate::{
+    time,
+    trie::{network, width},
+    EnumDef, Error, Field, ParseError,
+};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 /// Type signatures.
 pub enum Signature {
     IStr,
@@ -19,6 +28,24 @@ pub enum Signature {
     U32,
     U64,
     Bool,
+    /// IPv4 or IPv6 address.
+    Ip,
+    /// IPv4 address.
+    Ipv4,
+    /// IPv6 address.
+    Ipv6,
+    /// Network of addresses, as in `10.0.0.0/16`, a single address is a network of itself.
+    Cidr,
+    /// Calendar date, as in `2024-02-29`.
+    Date,
+    /// RFC 3339 instant, as in `2024-02-29T12:00:00Z`.
+    DateTime,
+    /// Length of time, as in `30d` or `12h`.
+    Duration,
+    /// Declared with `enum`, see `EnumDef`.
+    Enum(Arc<EnumDef>),
+    /// List of the inner type, as in `[u16]`.
+    List(Box<Signature>),
 }
 
 impl FromStr for Signature {
@@ -40,13 +67,28 @@ impl FromStr for Signature {
             "u32" => Self::U32,
             "u64" => Self::U64,
             "bool" => Self::Bool,
-            _ => return Err(Self::Err::UnrecognisedType { got: s.to_string() }),
+            "ip" => Self::Ip,
+            "ipv4" => Self::Ipv4,
+            "ipv6" => Self::Ipv6,
+            "cidr" => Self::Cidr,
+            "date" => Self::Date,
+            "datetime" => Self::DateTime,
+            "duration" => Self::Duration,
+            _ => match s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                Some(inner) => Self::list(Self::from_str(inner)?)
+                    .ok_or(Self::Err::UnrecognisedType { got: s.to_string() })?,
+                None => return Err(Self::Err::UnrecognisedType { got: s.to_string() }),
+            },
         })
     }
 }
 
 impl Display for Signature {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Self::List(inner) = self {
+            return f.write_fmt(format_args!("[{inner}]"));
+        }
+
         f.write_str(match self {
             Self::IStr => "istr",
             Self::UStr => "ustr",
@@ -62,6 +104,15 @@ impl Display for Signature {
             Self::U32 => "u32",
             Self::U64 => "u64",
             Self::Bool => "bool",
+            Self::Ip => "ip",
+            Self::Ipv4 => "ipv4",
+            Self::Ipv6 => "ipv6",
+            Self::Cidr => "cidr",
+            Self::Date => "date",
+            Self::DateTime => "datetime",
+            Self::Duration => "duration",
+            Self::Enum(def) => def.name(),
+            Self::List(_) => unreachable!("written above"),
         })
     }
 }
@@ -81,7 +132,7 @@ impl Signature {
                     location: location.to_string(),
                     line,
                     reason: ParseError::TypeError {
-                        target: *self,
+                        target: self.clone(),
                         value: s.to_string(),
                         reason: "cannot convert to char".to_string(),
                     },
@@ -94,7 +145,7 @@ impl Signature {
                         location: location.to_string(),
                         line,
                         reason: ParseError::TypeError {
-                            target: *self,
+                            target: self.clone(),
                             value: s.to_string(),
                             reason: e.to_string(),
                         },
@@ -108,7 +159,7 @@ impl Signature {
                         location: location.to_string(),
                         line,
                         reason: ParseError::TypeError {
-                            target: *self,
+                            target: self.clone(),
                             value: s.to_string(),
                             reason: e.to_string(),
                         },
@@ -122,7 +173,7 @@ impl Signature {
                         location: location.to_string(),
                         line,
                         reason: ParseError::TypeError {
-                            target: *self,
+                            target: self.clone(),
                             value: s.to_string(),
                             reason: e.to_string(),
                         },
@@ -136,7 +187,7 @@ impl Signature {
                         location: location.to_string(),
                         line,
                         reason: ParseError::TypeError {
-                            target: *self,
+                            target: self.clone(),
                             value: s.to_string(),
                             reason: e.to_string(),
                         },
@@ -150,7 +201,7 @@ impl Signature {
                         location: location.to_string(),
                         line,
                         reason: ParseError::TypeError {
-                            target: *self,
+                            target: self.clone(),
                             value: s.to_string(),
                             reason: e.to_string(),
                         },
@@ -164,7 +215,7 @@ impl Signature {
                         location: location.to_string(),
                         line,
                         reason: ParseError::TypeError {
-                            target: *self,
+                            target: self.clone(),
                             value: s.to_string(),
                             reason: e.to_string(),
                         },
@@ -178,7 +229,7 @@ impl Signature {
                         location: location.to_string(),
                         line,
                         reason: ParseError::TypeError {
-                            target: *self,
+                            target: self.clone(),
                             value: s.to_string(),
                             reason: e.to_string(),
                         },
@@ -192,7 +243,7 @@ impl Signature {
                         location: location.to_string(),
                         line,
                         reason: ParseError::TypeError {
-                            target: *self,
+                            target: self.clone(),
                             value: s.to_string(),
                             reason: e.to_string(),
                         },
@@ -206,7 +257,7 @@ impl Signature {
                         location: location.to_string(),
                         line,
                         reason: ParseError::TypeError {
-                            target: *self,
+                            target: self.clone(),
                             value: s.to_string(),
                             reason: e.to_string(),
                         },
@@ -220,7 +271,7 @@ impl Signature {
                         location: location.to_string(),
                         line,
                         reason: ParseError::TypeError {
-                            target: *self,
+                            target: self.clone(),
                             value: s.to_string(),
                             reason: e.to_string(),
                         },
@@ -234,13 +285,283 @@ impl Signature {
                         location: location.to_string(),
                         line,
                         reason: ParseError::TypeError {
-                            target: *self,
+                            target: self.clone(),
                             value: s.to_string(),
                             reason: e.to_string(),
                         },
                     })
                 }
             },
+            Self::Ip | Self::Ipv4 | Self::Ipv6 | Self::Cidr => match self.parse_ip(s) {
+                Ok(field) => field,
+                Err(reason) => {
+                    return Err(Error::ParseError {
+                        location: location.to_string(),
+                        line,
+                        reason: ParseError::TypeError {
+                            target: self.clone(),
+                            value: s.to_string(),
+                            reason,
+                        },
+                    })
+                }
+            },
+            Self::Date | Self::DateTime | Self::Duration => match self.parse_time(s) {
+                Ok(field) => field,
+                Err(reason) => {
+                    return Err(Error::ParseError {
+                        location: location.to_string(),
+                        line,
+                        reason: ParseError::TypeError {
+                            target: self.clone(),
+                            value: s.to_string(),
+                            reason,
+                        },
+                    })
+                }
+            },
+            Self::List(inner) => {
+                let items = s.strip_prefix('[').and_then(|s| s.strip_suffix(']'));
+                match items {
+                    Some(items) => Field::List(
+                        Self::split_list(items)
+                            .iter()
+                            .map(|item| inner.parse(item, location, line))
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    None => {
+                        return Err(Error::ParseError {
+                            location: location.to_string(),
+                            line,
+                            reason: ParseError::TypeError {
+                                target: self.clone(),
+                                value: s.to_string(),
+                                reason: "not in brackets".to_string(),
+                            },
+                        })
+                    }
+                }
+            }
+            Self::Enum(def) => match def.position(s) {
+                Some(i) => Field::Enum(def.clone(), i),
+                None => {
+                    return Err(Error::ParseError {
+                        location: location.to_string(),
+                        line,
+                        reason: ParseError::TypeError {
+                            target: self.clone(),
+                            value: s.to_string(),
+                            reason: format!("not one of {}", def.variants().join(", ")),
+                        },
+                    })
+                }
+            },
+        })
+    }
+}
+
+impl Signature {
+    /// Convert a typed field to this type, returns `None` if the value cannot be represented without loss.
+    /// - istr and ustr are interchangeable.
+    /// - Integers convert to any integer or float type that can hold the value.
+    /// - Strings convert to enum values of the same name.
+    /// - Lists convert each element.
+    /// - Strings convert to addresses and networks they are written as, an address to a network of itself.
+    /// - Strings convert to dates, datetimes and durations they are written as, a date to midnight UTC.
+    /// - Patterns convert their bounds, see `SchemaOne::cast` for pattern columns.
+    /// - Null stays null, see `SchemaOne::cast` for nullable columns.
+    pub fn cast(&self, field: &Field) -> Option<Field> {
+        match field {
+            Field::Null => return Some(Field::Null),
+            Field::Pattern(pattern) => return Some(Field::from(pattern.cast(self)?)),
+            _ => {}
+        }
+
+        Some(match self {
+            Self::IStr => Field::IStr(field.as_str()?.to_string()),
+            Self::UStr => {
+                let s = field.as_str()?;
+                Field::UStr {
+                    original: s.to_string(),
+                    lower: s.to_lowercase(),
+                }
+            }
+            Self::Char => Field::Char(field.as_char()?),
+            Self::Bool => Field::Bool(field.as_bool()?),
+            Self::F32 => match field {
+                Field::F32(f) => Field::F32(*f),
+                Field::F64(f) if f.is_nan() || *f as f32 as f64 == *f => Field::F32(*f as f32),
+                _ => {
+                    let i = Self::integer_value(field)?;
+                    let f = i as f32;
+                    if f as i128 != i {
+                        return None;
+                    }
+                    Field::F32(f)
+                }
+            },
+            Self::F64 => match field {
+                Field::F32(f) => Field::F64(*f as f64),
+                Field::F64(f) => Field::F64(*f),
+                _ => {
+                    let i = Self::integer_value(field)?;
+                    let f = i as f64;
+                    if f as i128 != i {
+                        return None;
+                    }
+                    Field::F64(f)
+                }
+            },
+            Self::I8 => Field::I8(Self::integer_value(field)?.try_into().ok()?),
+            Self::I16 => Field::I16(Self::integer_value(field)?.try_into().ok()?),
+            Self::I32 => Field::I32(Self::integer_value(field)?.try_into().ok()?),
+            Self::I64 => Field::I64(Self::integer_value(field)?.try_into().ok()?),
+            Self::U8 => Field::U8(Self::integer_value(field)?.try_into().ok()?),
+            Self::U16 => Field::U16(Self::integer_value(field)?.try_into().ok()?),
+            Self::U32 => Field::U32(Self::integer_value(field)?.try_into().ok()?),
+            Self::U64 => Field::U64(Self::integer_value(field)?.try_into().ok()?),
+            Self::Enum(def) => match field {
+                Field::Enum(other, i) if other.name() == def.name() => Field::Enum(def.clone(), *i),
+                _ => Field::Enum(def.clone(), def.position(field.as_str()?)?),
+            },
+            Self::Ip | Self::Ipv4 | Self::Ipv6 | Self::Cidr => match (self, field) {
+                (Self::Ip, Field::Ip(addr))
+                | (Self::Ipv4, Field::Ip(addr @ IpAddr::V4(_)))
+                | (Self::Ipv6, Field::Ip(addr @ IpAddr::V6(_))) => Field::Ip(*addr),
+                (Self::Cidr, Field::Cidr(addr, len)) => Field::Cidr(*addr, *len),
+                (Self::Cidr, Field::Ip(addr)) => Field::Cidr(*addr, width(*addr)),
+                _ => self.parse_ip(field.as_str()?).ok()?,
+            },
+            Self::Date | Self::DateTime | Self::Duration => match (self, field) {
+                (Self::Date, Field::Date(days)) => Field::Date(*days),
+                (Self::DateTime, Field::DateTime { .. }) => field.clone(),
+                (Self::DateTime, Field::Date(days)) => Field::DateTime {
+                    secs: days * 86400,
+                    nanos: 0,
+                    offset: 0,
+                },
+                (Self::Duration, Field::Duration(secs)) => Field::Duration(*secs),
+                _ => self.parse_time(field.as_str()?).ok()?,
+            },
+            Self::List(inner) => Field::List(
+                field
+                    .as_list()?
+                    .iter()
+                    .map(|item| inner.cast(item))
+                    .collect::<Option<_>>()?,
+            ),
+        })
+    }
+
+    /// Returns true if records are indexed in order of this type, so ranges can be searched.
+    pub fn ordered(&self) -> bool {
+        !matches!(self, Self::Bool)
+    }
+
+    /// Returns type of elements for list types.
+    pub fn element(&self) -> Option<&Signature> {
+        match self {
+            Self::List(inner) => Some(inner),
+            _ => None,
+        }
+    }
+
+    /// Constructs a list type, `None` if elements would be lists.
+    pub fn list(inner: Signature) -> Option<Self> {
+        (!matches!(inner, Self::List(_))).then(|| Self::List(Box::new(inner)))
+    }
+
+    /// Returns true for integer and float types.
+    pub fn numeric(&self) -> bool {
+        self.integer() || matches!(self, Self::F32 | Self::F64)
+    }
+
+    /// Returns true for integer types.
+    pub fn integer(&self) -> bool {
+        matches!(
+            self,
+            Self::I8
+                | Self::I16
+                | Self::I32
+                | Self::I64
+                | Self::U8
+                | Self::U16
+                | Self::U32
+                | Self::U64
+        )
+    }
+
+    /// Parse an address or network of this type, returns the reason on failure.
+    fn parse_ip(&self, s: &str) -> Result<Field, String> {
+        Ok(match self {
+            Self::Ipv4 => Field::Ip(IpAddr::V4(
+                s.parse::<Ipv4Addr>().map_err(|e| e.to_string())?,
+            )),
+            Self::Ipv6 => Field::Ip(IpAddr::V6(
+                s.parse::<Ipv6Addr>().map_err(|e| e.to_string())?,
+            )),
+            Self::Cidr => {
+                let (addr, len) = match s.split_once('/') {
+                    Some((addr, len)) => (addr, Some(len)),
+                    None => (s, None),
+                };
+                let addr = addr.parse::<IpAddr>().map_err(|e| e.to_string())?;
+                let len = match len {
+                    Some(len) => match len.parse::<u8>() {
+                        Ok(len) if len <= width(addr) => len,
+                        _ => return Err("invalid prefix length".to_string()),
+                    },
+                    None => width(addr),
+                };
+                if network(addr, len) != addr {
+                    return Err("address has bits set after the prefix".to_string());
+                }
+                Field::Cidr(addr, len)
+            }
+            _ => Field::Ip(s.parse::<IpAddr>().map_err(|e| e.to_string())?),
         })
     }
+
+    /// Parse a date, datetime or duration of this type, returns the reason on failure.
+    fn parse_time(&self, s: &str) -> Result<Field, String> {
+        Ok(match self {
+            Self::Date => Field::Date(time::parse_date(s)?),
+            Self::DateTime => {
+                let (secs, nanos, offset) = time::parse_datetime(s)?;
+                Field::DateTime {
+                    secs,
+                    nanos,
+                    offset,
+                }
+            }
+            _ => Field::Duration(time::parse_duration(s)?),
+        })
+    }
+
+    /// Split elements written in a list, commas escaped with a backslash do not split.
+    fn split_list(s: &str) -> Vec<String> {
+        let mut items = Vec::new();
+        let mut buf = String::new();
+        let mut chars = s.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => buf.extend(chars.next()),
+                ',' => items.push(std::mem::take(&mut buf).trim().to_string()),
+                _ => buf.push(c),
+            }
+        }
+
+        if !items.is_empty() || !buf.trim().is_empty() {
+            items.push(buf.trim().to_string());
+        }
+        items
+    }
+
+    fn integer_value(field: &Field) -> Option<i128> {
+        field
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| field.as_u64().map(i128::from))
+    }
 }
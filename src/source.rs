@@ -0,0 +1,194 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::{Document, Error, Field, Pass, SyntaxTree};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Represents a file read while loading a document.
+pub struct Source {
+    name: String,
+    path: PathBuf,
+    content: String,
+    on_disk: bool,
+    included_at: Option<(String, u32)>,
+}
+
+impl Source {
+    /// Constructs new self.
+    pub fn new(name: String, path: PathBuf, content: String, on_disk: bool) -> Self {
+        Self {
+            name,
+            path,
+            content,
+            on_disk,
+            included_at: None,
+        }
+    }
+
+    /// Returns file name and line of the `include` that read the file, `None` for the file parsed first.
+    pub fn included_at(&self) -> Option<(&str, u32)> {
+        self.included_at
+            .as_ref()
+            .map(|(name, line)| (name.as_str(), *line))
+    }
+
+    pub(crate) fn set_included_at(&mut self, name: String, line: u32) {
+        self.included_at = Some((name, line));
+    }
+
+    /// Returns file name as used in record locations.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns file path.
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Returns file content as loaded.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Returns true if the file is read from disk, rather than provided as a string.
+    pub fn on_disk(&self) -> bool {
+        self.on_disk
+    }
+
+    /// Apply changes of records in document to file content, comments and layout are preserved.
+    /// - Amended records are changed in place.
+    /// - Removed records are taken out of their lines.
+    /// - Inserted records are written after the last record of the same type in their scope, in the order they are inserted.
+    pub fn rewrite(&self, doc: &Document) -> Result<String, Error> {
+        let parse_error = |(line, reason)| Error::ParseError {
+            location: self.name.clone(),
+            line,
+            reason,
+        };
+
+        let mut tree = SyntaxTree::parse(&self.content).map_err(parse_error)?;
+        let cleared = Pass::clear_comment(&self.content).map_err(parse_error)?;
+
+        let mut existing = HashMap::new();
+        let mut inserted = Vec::new();
+
+        // records of a type keep insertion order, types are taken by label
+        let mut sets = doc.record_sets().collect::<Vec<_>>();
+        sets.sort_by_key(|rs| rs.schema().label());
+
+        for record in sets.into_iter().flat_map(|rs| rs.records()) {
+            if Pass::split_location(record.location()).0 != self.name {
+                continue;
+            }
+
+            if record.line() == 0 {
+                inserted.push(record);
+            } else {
+                existing.insert((record.location(), record.line()), record);
+            }
+        }
+
+        let mut scope: Option<String> = None;
+        // last line with a definition or record of the type in a scope
+        let mut after_label: HashMap<(Option<String>, String), u32> = HashMap::new();
+        // last line with any statement in a scope
+        let mut after_scope: HashMap<Option<String>, u32> = HashMap::new();
+
+        for (line, no) in cleared.lines().zip(1_u32..) {
+            let line = line.trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some((label, _)) = Pass::split_col(line) {
+                after_label.insert((scope.clone(), label.trim().to_string()), no);
+                after_scope.insert(scope.clone(), no);
+                continue;
+            }
+
+            let quoted =
+                Pass::split_args_quoted(line).map_err(|reason| parse_error((no, reason)))?;
+            let words = quoted
+                .iter()
+                .map(|(arg, _)| arg.as_str())
+                .collect::<Vec<_>>();
+
+            match words[0].to_lowercase().as_str() {
+                "include" | "enum" => {}
+                "scope" => scope = words.get(1).map(|s| s.to_string()),
+                label => {
+                    let location = match &scope {
+                        Some(scope) => format!("{}<{scope}>", self.name),
+                        None => self.name.clone(),
+                    };
+
+                    match existing.get(&(location.as_str(), no)) {
+                        Some(record) if record.label() == label => {
+                            let schema = doc.schema(record.label());
+                            let parsed = schema
+                                .and_then(|schema| {
+                                    let args = quoted[1..]
+                                        .iter()
+                                        .map(|(arg, quoted)| (arg.as_str(), *quoted))
+                                        .collect();
+                                    schema.parse_quoted(args, &location, no).ok()
+                                })
+                                .unwrap_or_default();
+
+                            let written = words.len() - 1;
+                            for (i, field) in record.args().iter().enumerate().take(written) {
+                                if parsed.get(i) != Some(field) {
+                                    tree.set_word(no, i + 1, &field.to_rules_string());
+                                }
+                            }
+
+                            // columns left out of the line are written up to the last one changed from its default
+                            let columns = schema.map(|s| s.columns()).unwrap_or_default();
+                            if let Some(last) = (written..record.args().len()).rev().find(|&i| {
+                                columns.get(i).and_then(|c| c.default()) != Some(&record.args()[i])
+                            }) {
+                                for field in &record.args()[written..=last] {
+                                    tree.append_word(no, &field.to_rules_string());
+                                }
+                            }
+                            after_label.insert((scope.clone(), label.to_string()), no);
+                        }
+                        _ => {
+                            tree.remove(no);
+                            continue;
+                        }
+                    }
+                }
+            }
+
+            after_scope.insert(scope.clone(), no);
+        }
+
+        for record in inserted {
+            let record_scope = Pass::split_location(record.location()).1.map(String::from);
+            let words = std::iter::once(record.label().to_string())
+                .chain(record.args().iter().map(Field::to_rules_string))
+                .collect::<Vec<_>>();
+
+            if let Some(line) = after_label.get(&(record_scope.clone(), record.label().to_string()))
+            {
+                tree.insert_after(*line, &words, true);
+            } else if let Some(line) = after_scope.get(&record_scope) {
+                tree.insert_after(*line, &words, false);
+            } else {
+                if record_scope != scope {
+                    tree.push(
+                        &std::iter::once("scope".to_string())
+                            .chain(record_scope.iter().map(|s| Field::escape_word(s)))
+                            .collect::<Vec<_>>(),
+                    );
+                    scope = record_scope;
+                }
+                tree.push(&words);
+            }
+        }
+
+        Ok(tree.to_string())
+    }
+}
@@ -0,0 +1,412 @@
+use std::fmt::Display;
+
+use crate::{pass::QUOTES, ParseError, Pass};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Kinds of token in a rules file.
+pub enum TokenKind {
+    /// An argument, quotes and escapes are kept as written.
+    Word,
+    /// Spaces and tabs between words.
+    Space,
+    /// Single or multi-line comment.
+    Comment,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A piece of source text.
+pub struct Token {
+    kind: TokenKind,
+    text: String,
+    line: u32,
+}
+
+impl Token {
+    /// Returns token kind.
+    pub fn kind(&self) -> TokenKind {
+        self.kind
+    }
+
+    /// Returns source text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns line number the token starts at, `0` if it is not from source.
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A line of source text, multi-line comments may extend it over several lines.
+pub struct SyntaxLine {
+    tokens: Vec<Token>,
+    newline: bool,
+}
+
+impl SyntaxLine {
+    /// Returns tokens in line.
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    fn has_line(&self, line: u32) -> bool {
+        self.tokens.iter().any(|t| t.line == line)
+    }
+
+    fn is_inserted(&self) -> bool {
+        self.tokens.iter().all(|t| t.line == 0)
+    }
+
+    /// Returns the column each word of a source line starts at.
+    fn columns(&self, line: u32) -> Vec<usize> {
+        let mut columns = Vec::new();
+        let mut column = 0;
+
+        for token in self.tokens.iter() {
+            if token.line == line && token.kind == TokenKind::Word {
+                columns.push(column);
+            }
+
+            match token.text.rsplit_once('\n') {
+                Some((_, after)) => column = after.chars().count(),
+                None => column += token.text.chars().count(),
+            }
+        }
+
+        columns
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Lossless representation of a rules file, writing it out reproduces the file exactly.
+pub struct SyntaxTree {
+    lines: Vec<SyntaxLine>,
+}
+
+impl Display for SyntaxTree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in self.lines.iter() {
+            for token in line.tokens.iter() {
+                f.write_str(&token.text)?;
+            }
+            if line.newline {
+                f.write_str("\n")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SyntaxTree {
+    /// Split file content into tokens, comments are located the same way as `Pass::clear_comment`.
+    pub fn parse(s: &str) -> Result<Self, (u32, ParseError)> {
+        let mut comments = Pass::find_comments(s)?.into_iter().peekable();
+
+        let mut lines = Vec::new();
+        let mut tokens = Vec::new();
+        let mut line_no = 1;
+        let mut i = 0;
+
+        while i < s.len() {
+            if let Some(range) = comments.next_if(|range| range.start == i) {
+                let text = &s[range.clone()];
+                tokens.push(Token {
+                    kind: TokenKind::Comment,
+                    text: text.to_string(),
+                    line: line_no,
+                });
+                line_no += text.matches('\n').count() as u32;
+                i = range.end;
+                continue;
+            }
+
+            let next_comment = comments.peek().map_or(s.len(), |range| range.start);
+            let rest = &s[i..next_comment];
+
+            let len = match rest.chars().next() {
+                Some('\n') => {
+                    lines.push(SyntaxLine {
+                        tokens: std::mem::take(&mut tokens),
+                        newline: true,
+                    });
+                    line_no += 1;
+                    i += 1;
+                    continue;
+                }
+                Some(' ' | '\t' | '\r') => {
+                    let len = rest
+                        .find(|c| !matches!(c, ' ' | '\t' | '\r'))
+                        .unwrap_or(rest.len());
+                    tokens.push(Token {
+                        kind: TokenKind::Space,
+                        text: rest[..len].to_string(),
+                        line: line_no,
+                    });
+                    len
+                }
+                _ => {
+                    let len = Self::word_len(rest);
+                    tokens.push(Token {
+                        kind: TokenKind::Word,
+                        text: rest[..len].to_string(),
+                        line: line_no,
+                    });
+                    len
+                }
+            };
+
+            i += len;
+        }
+
+        lines.push(SyntaxLine {
+            tokens,
+            newline: false,
+        });
+
+        Ok(Self { lines })
+    }
+
+    /// Length of the word at the start of a string, spaces in quotes do not end a word.
+    fn word_len(s: &str) -> usize {
+        let mut in_string = None;
+        let mut escaped = false;
+
+        for (i, c) in s.char_indices() {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if let Some(quote) = in_string {
+                if c == quote {
+                    in_string = None;
+                }
+            } else if QUOTES.contains(&c) {
+                in_string = Some(c);
+            } else if matches!(c, ' ' | '\t' | '\r' | '\n') {
+                return i;
+            }
+        }
+
+        s.len()
+    }
+
+    /// Returns lines in file.
+    pub fn lines(&self) -> &[SyntaxLine] {
+        &self.lines
+    }
+
+    fn find(&self, line: u32) -> Option<usize> {
+        self.lines.iter().position(|l| l.has_line(line))
+    }
+
+    /// Returns words on a source line.
+    pub fn words(&self, line: u32) -> Vec<&str> {
+        self.lines
+            .iter()
+            .flat_map(|l| l.tokens.iter())
+            .filter(|t| t.line == line && t.kind == TokenKind::Word)
+            .map(|t| t.text.as_str())
+            .collect()
+    }
+
+    /// Replace the nth word on a source line, the space after it is resized to keep the following columns in place.
+    pub fn set_word(&mut self, line: u32, n: usize, text: &str) -> bool {
+        let Some(index) = self.find(line) else {
+            return false;
+        };
+        let tokens = &mut self.lines[index].tokens;
+
+        let Some(pos) = tokens
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.line == line && t.kind == TokenKind::Word)
+            .nth(n)
+            .map(|(pos, _)| pos)
+        else {
+            return false;
+        };
+
+        let old_len = tokens[pos].text.chars().count();
+        let new_len = text.chars().count();
+        tokens[pos].text = text.to_string();
+
+        if pos + 2 < tokens.len() && tokens[pos + 1].kind == TokenKind::Space {
+            let space = &mut tokens[pos + 1].text;
+            let space_len = space.chars().count();
+            let target = (space_len + old_len).saturating_sub(new_len).max(1);
+            *space = " ".repeat(target);
+        }
+
+        true
+    }
+
+    /// Add a word after the last word on a source line, separated by a single space.
+    pub fn append_word(&mut self, line: u32, text: &str) -> bool {
+        let Some(index) = self.find(line) else {
+            return false;
+        };
+        let tokens = &mut self.lines[index].tokens;
+
+        let Some(pos) = tokens
+            .iter()
+            .rposition(|t| t.line == line && t.kind == TokenKind::Word)
+        else {
+            return false;
+        };
+
+        tokens.splice(
+            pos + 1..pos + 1,
+            [
+                Token {
+                    kind: TokenKind::Space,
+                    text: " ".to_string(),
+                    line,
+                },
+                Token {
+                    kind: TokenKind::Word,
+                    text: text.to_string(),
+                    line,
+                },
+            ],
+        );
+
+        true
+    }
+
+    /// Remove all words on a source line.
+    /// - The whole line is removed if it has no other words and no multi-line comments.
+    /// - Otherwise other words and comments are kept.
+    pub fn remove(&mut self, line: u32) -> bool {
+        let Some(index) = self.find(line) else {
+            return false;
+        };
+        let syntax_line = &mut self.lines[index];
+
+        let keep_line = syntax_line.tokens.iter().any(|t| {
+            (t.kind == TokenKind::Word && t.line != line)
+                || (t.kind == TokenKind::Comment && t.text.contains('\n'))
+        });
+
+        if !keep_line {
+            if syntax_line.newline || index == 0 {
+                self.lines.remove(index);
+            } else {
+                self.lines.remove(index);
+                self.lines[index - 1].newline = false;
+            }
+            return true;
+        }
+
+        let mut removing = false;
+        syntax_line.tokens.retain(|t| {
+            match t.kind {
+                TokenKind::Word => removing = t.line == line,
+                TokenKind::Space => return !removing,
+                TokenKind::Comment => removing = false,
+            }
+            !removing
+        });
+
+        while syntax_line
+            .tokens
+            .last()
+            .is_some_and(|t| t.kind == TokenKind::Space)
+        {
+            syntax_line.tokens.pop();
+        }
+
+        true
+    }
+
+    /// Insert a line of words after a source line, and after any lines already inserted there.
+    /// - If `align` is set, words are aligned to the columns of the source line.
+    /// - Otherwise only the indentation is kept.
+    pub fn insert_after(&mut self, line: u32, words: &[String], align: bool) -> bool {
+        let Some(mut index) = self.find(line) else {
+            return false;
+        };
+
+        let mut columns = self.lines[index].columns(line);
+        if !align {
+            columns.truncate(1);
+        }
+
+        while self
+            .lines
+            .get(index + 1)
+            .is_some_and(|l| !l.tokens.is_empty() && l.is_inserted())
+        {
+            index += 1;
+        }
+
+        let newline = self.lines[index].newline;
+        self.lines[index].newline = true;
+        self.lines.insert(
+            index + 1,
+            SyntaxLine {
+                tokens: Self::layout(words, &columns),
+                newline,
+            },
+        );
+
+        true
+    }
+
+    /// Append a line of words at the end of file.
+    pub fn push(&mut self, words: &[String]) {
+        let line = SyntaxLine {
+            tokens: Self::layout(words, &[]),
+            newline: true,
+        };
+
+        match self.lines.last_mut() {
+            Some(last) if last.tokens.is_empty() && !last.newline => {
+                let index = self.lines.len() - 1;
+                self.lines.insert(index, line);
+            }
+            Some(last) => {
+                last.newline = true;
+                self.lines.push(SyntaxLine {
+                    newline: false,
+                    ..line
+                });
+            }
+            None => self.lines.push(line),
+        }
+    }
+
+    /// Create tokens for a line of words, each word starts at its column where possible.
+    fn layout(words: &[String], columns: &[usize]) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut column = 0;
+
+        for (i, word) in words.iter().enumerate() {
+            let target = columns.get(i).copied().unwrap_or(0);
+            let space = if i == 0 {
+                target
+            } else {
+                target.saturating_sub(column).max(1)
+            };
+
+            if space > 0 {
+                tokens.push(Token {
+                    kind: TokenKind::Space,
+                    text: " ".repeat(space),
+                    line: 0,
+                });
+            }
+            tokens.push(Token {
+                kind: TokenKind::Word,
+                text: word.clone(),
+                line: 0,
+            });
+
+            column += space + word.chars().count();
+        }
+
+        tokens
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{filter, Aggregate, Error, Field, Signature};
+
+use super::doc;
+
+const CONTENT: &str = r#"
+whitelist:  ustr    istr        u64     i8      f32     bool
+whitelist   joe     127.0.0.1   100     -3      0.5     true
+whitelist   bob     127.0.0.1   200     5       1.5     false
+whitelist   Joe     127.0.0.3   300     -7      2.5     true
+whitelist   carol   127.0.0.4   18446744073709551615    0   0   true
+"#;
+
+#[test]
+fn _1() {
+    let doc = doc(CONTENT);
+    assert_eq!(doc.count(filter!("whitelist"; *, *, *, *, *, *)), Ok(4));
+    assert_eq!(
+        doc.count(filter!("whitelist"; *, "127.0.0.1", *, *, *, *)),
+        Ok(2)
+    );
+    assert_eq!(
+        doc.count(filter!("whitelist"; "nobody", *, *, *, *, *)),
+        Ok(0)
+    );
+}
+
+#[test]
+fn _2() {
+    let doc = doc(CONTENT);
+    let values = doc
+        .aggregate(
+            filter!("whitelist"; *, *, <= 300, *, *, *),
+            &[
+                Aggregate::Count,
+                Aggregate::Sum(2),
+                Aggregate::Sum(3),
+                Aggregate::Sum(4),
+                Aggregate::Min(3),
+                Aggregate::Max(1),
+                Aggregate::Average(2),
+            ],
+        )
+        .unwrap();
+    assert_eq!(
+        values,
+        vec![
+            Some(Field::U64(3)),
+            Some(Field::U64(600)),
+            Some(Field::I64(-5)),
+            Some(Field::F64(4.5)),
+            Some(Field::I8(-7)),
+            Some(Field::IStr("127.0.0.3".to_string())),
+            Some(Field::F64(200.0)),
+        ]
+    );
+
+    let values = doc
+        .aggregate(
+            filter!("whitelist"; "nobody", *, *, *, *, *),
+            &[
+                Aggregate::Count,
+                Aggregate::Sum(2),
+                Aggregate::Max(2),
+                Aggregate::Average(4),
+            ],
+        )
+        .unwrap();
+    assert_eq!(
+        values,
+        vec![Some(Field::U64(0)), Some(Field::U64(0)), None, None]
+    );
+}
+
+#[test]
+fn _3() {
+    let doc = doc(CONTENT);
+    let groups = doc
+        .group_by(
+            filter!("whitelist"; *, *, <= 300, *, *, *),
+            &[0],
+            &[Aggregate::Count, Aggregate::Sum(2)],
+        )
+        .unwrap();
+    let groups = groups
+        .iter()
+        .map(|g| {
+            (
+                g.key()[0].as_str().unwrap().to_lowercase(),
+                g.values().to_vec(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        groups,
+        vec![
+            (
+                "bob".to_string(),
+                vec![Some(Field::U64(1)), Some(Field::U64(200))]
+            ),
+            (
+                "joe".to_string(),
+                vec![Some(Field::U64(2)), Some(Field::U64(400))]
+            ),
+        ]
+    );
+
+    let groups = doc
+        .group_by(
+            filter!("whitelist"; *, *, *, *, *, *),
+            &[5, 1],
+            &[Aggregate::Count],
+        )
+        .unwrap();
+    assert_eq!(groups.len(), 4);
+    assert_eq!(groups[0].key()[0], Field::Bool(false));
+}
+
+#[test]
+fn _4() {
+    let doc = doc(CONTENT);
+    let all = filter!("whitelist"; *, *, *, *, *, *);
+    assert_eq!(
+        doc.aggregate(all.clone(), &[Aggregate::Sum(2)]),
+        Err(Error::AggregateOverflow {
+            label: "whitelist".to_string(),
+            aggregate: Aggregate::Sum(2)
+        })
+    );
+    assert_eq!(
+        doc.aggregate(all.clone(), &[Aggregate::Average(0)]),
+        Err(Error::AggregateMismatch {
+            label: "whitelist".to_string(),
+            aggregate: Aggregate::Average(0),
+            got: Signature::UStr
+        })
+    );
+    assert!(matches!(
+        doc.aggregate(all.clone(), &[Aggregate::Min(6)]),
+        Err(Error::UnknownColumn { .. })
+    ));
+    assert!(matches!(
+        doc.group_by(all, &[6], &[Aggregate::Count]),
+        Err(Error::UnknownColumn { .. })
+    ));
+}
@@ -91,3 +91,15 @@ fn e() {
     let res = Pass::clear_comment("one two \\\\'# three four'").unwrap();
     assert_eq!(res.as_str(), "one two \\\\'# three four'")
 }
+
+#[test]
+fn f() {
+    let res = Pass::clear_comment("one/two *three").unwrap();
+    assert_eq!(res.as_str(), "one/two *three")
+}
+
+#[test]
+fn g() {
+    let res = Pass::clear_comment("one/* two * three / four */five").unwrap();
+    assert_eq!(res.as_str(), "one five")
+}
@@ -0,0 +1,159 @@
+use std::ops::Bound;
+
+use crate::{Condition, Document, Error, Field, Filter, Predicate};
+
+use super::doc;
+
+const CONTENT: &str = r#"
+whitelist:  ustr    istr        u64
+whitelist   joe     127.0.0.1   123456
+whitelist   bob     127.0.0.2   100
+whitelist   Alice   127.0.0.3   123459
+whitelist   carol   127.0.0.1   0
+whitelist   bob     127.0.0.5   123457
+"#;
+
+fn names(doc: &Document, cond: Condition) -> Vec<String> {
+    let mut found = doc
+        .find(Filter::with_condition("whitelist".to_string(), cond))
+        .unwrap()
+        .into_iter()
+        .map(|r| r.args()[0].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    found.sort();
+    found
+}
+
+fn name(s: &str) -> Condition {
+    Condition::field(0, Predicate::Eq(Field::IStr(s.to_string())))
+}
+
+fn ip(s: &str) -> Predicate {
+    Predicate::Eq(Field::IStr(s.to_string()))
+}
+
+fn expired() -> Condition {
+    Condition::field(2, Predicate::Lt(Field::U64(1000)))
+}
+
+#[test]
+fn _1() {
+    let doc = doc(CONTENT);
+    assert_eq!(
+        names(&doc, name("joe").or(name("bob")).and(!expired())),
+        vec!["bob", "joe"]
+    );
+    assert_eq!(names(&doc, name("bob").and(expired())), vec!["bob"]);
+    assert_eq!(names(&doc, !name("bob")), vec!["Alice", "carol", "joe"]);
+    assert_eq!(names(&doc, !!name("bob")), vec!["bob", "bob"]);
+}
+
+#[test]
+fn _2() {
+    let doc = doc(CONTENT);
+    assert_eq!(
+        names(
+            &doc,
+            Condition::field(1, ip("127.0.0.1").or(ip("127.0.0.3")))
+        ),
+        vec!["Alice", "carol", "joe"]
+    );
+    assert_eq!(
+        names(
+            &doc,
+            Condition::field(1, ip("127.0.0.1").and(!ip("127.0.0.1")))
+        ),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        names(
+            &doc,
+            Condition::field(
+                2,
+                Predicate::Gt(Field::U64(10)).and(Predicate::Le(Field::U64(123456)))
+            )
+        ),
+        vec!["bob", "joe"]
+    );
+}
+
+#[test]
+fn _3() {
+    let doc = doc(CONTENT);
+    let filter = Filter::with_predicates(
+        "whitelist".to_string(),
+        vec![Predicate::Any, ip("127.0.0.1"), Predicate::Any],
+    )
+    .and(!expired());
+    assert_eq!(doc.find(filter).unwrap().len(), 1);
+
+    let filter =
+        Filter::with_predicates("whitelist".to_string(), vec![ip("127.0.0.2")]).or(name("joe"));
+    assert!(matches!(
+        doc.find(filter),
+        Err(Error::FilterMismatch { .. })
+    ));
+    assert!(matches!(
+        doc.find(Filter::with_condition(
+            "whitelist".to_string(),
+            Condition::field(3, Predicate::Any)
+        )),
+        Err(Error::FilterMismatch { .. })
+    ));
+}
+
+#[test]
+fn _4() {
+    let cond = name("joe").or(name("bob")).and(!expired());
+    assert!(!cond.exact());
+    assert_eq!(
+        cond.narrow(3),
+        vec![
+            Predicate::Or(vec![
+                Predicate::Eq(Field::IStr("joe".to_string())),
+                Predicate::Eq(Field::IStr("bob".to_string()))
+            ]),
+            Predicate::Any,
+            Predicate::Any,
+        ]
+    );
+
+    let cond = name("joe").and(expired());
+    assert!(cond.exact());
+    assert_eq!(cond.narrow(3)[2], Predicate::Lt(Field::U64(1000)));
+    assert_eq!(
+        Filter::with_condition("whitelist".to_string(), cond).to_string(),
+        "whitelist (0=\"joe\" and 2<1000)"
+    );
+}
+
+#[test]
+fn _5() {
+    let (one, five, nine) = (Field::U64(1), Field::U64(5), Field::U64(9));
+    assert_eq!(
+        Predicate::Gt(one.clone())
+            .and(Predicate::Le(nine.clone()))
+            .bounds(),
+        Some((Bound::Excluded(&one), Bound::Included(&nine)))
+    );
+    assert_eq!(
+        Predicate::Eq(one.clone())
+            .or(Predicate::Lt(five.clone()))
+            .or(Predicate::Eq(nine.clone()))
+            .bounds(),
+        Some((Bound::Unbounded, Bound::Included(&nine)))
+    );
+    assert_eq!(
+        Predicate::Gt(five.clone())
+            .and(Predicate::Lt(five.clone()))
+            .bounds(),
+        None
+    );
+    assert_eq!(
+        Predicate::Ge(five.clone())
+            .and(Predicate::Le(five.clone()))
+            .bounds(),
+        Some((Bound::Included(&five), Bound::Included(&five)))
+    );
+    assert_eq!(Predicate::None.or(Predicate::None).bounds(), None);
+}
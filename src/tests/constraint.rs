@@ -0,0 +1,151 @@
+use std::ops::Bound;
+
+use crate::{filter, Constraint, Document, Error, Field, ParseError};
+
+use super::load;
+
+const DEFINITION: &str =
+    "perm:   ustr len 1..=8   ustr in {read, write}   u8 1..=100   istr glob *.example.com";
+
+fn perms(records: &str) -> Result<Document, Error> {
+    load(&format!("{DEFINITION}\n{records}"))
+}
+
+fn violation(records: &str) -> Option<(u32, usize, String)> {
+    match perms(records) {
+        Err(Error::ParseError {
+            line,
+            reason:
+                ParseError::ConstraintViolation {
+                    column, constraint, ..
+                },
+            ..
+        }) => Some((line, column, constraint.to_string())),
+        _ => None,
+    }
+}
+
+#[test]
+fn _1() {
+    let doc = perms("perm joe READ 1 www.example.com\nperm bob write 100 a.example.com").unwrap();
+    let schema = doc.schema("perm").unwrap();
+    assert_eq!(
+        schema.columns()[1].constraints(),
+        &[Constraint::In(vec![
+            Field::UStr {
+                original: "read".to_string(),
+                lower: "read".to_string()
+            },
+            Field::UStr {
+                original: "write".to_string(),
+                lower: "write".to_string()
+            }
+        ])]
+    );
+    assert_eq!(
+        schema.columns()[2].constraints(),
+        &[Constraint::Range(
+            Bound::Included(Field::U8(1)),
+            Bound::Included(Field::U8(100))
+        )]
+    );
+    assert_eq!(
+        schema.to_rules_string(),
+        "perm: ustr len 1..=8 ustr in {read, write} u8 1..=100 istr glob *.example.com"
+    );
+    assert_eq!(doc.find(filter!("perm"; *, *, *, *)).unwrap().len(), 2);
+}
+
+#[test]
+fn _2() {
+    assert_eq!(
+        violation("perm joe read 1 www.example.com\nperm alexander_the_great read 1 a.example.com"),
+        Some((3, 0, "len 1..=8".to_string()))
+    );
+    assert_eq!(
+        violation("perm joe execute 1 www.example.com"),
+        Some((2, 1, "in {read, write}".to_string()))
+    );
+    assert_eq!(
+        violation("perm joe read 0 www.example.com"),
+        Some((2, 2, "1..=100".to_string()))
+    );
+    assert_eq!(
+        violation("perm joe read 1 www.example.org"),
+        Some((2, 3, "glob *.example.com".to_string()))
+    );
+}
+
+#[test]
+fn _3() {
+    let mut doc = perms("").unwrap();
+    doc.insert(
+        "perm",
+        vec![
+            Field::from("joe"),
+            Field::from("Write"),
+            Field::U8(50),
+            Field::from("x.example.com"),
+        ],
+    )
+    .unwrap();
+    assert!(matches!(
+        doc.insert(
+            "perm",
+            vec![
+                Field::from("bob"),
+                Field::from("write"),
+                Field::U8(101),
+                Field::from("x.example.com"),
+            ],
+        ),
+        Err(Error::RecordMismatch { .. })
+    ));
+    assert!(matches!(
+        doc.update(
+            filter!("perm"; "joe", *, *, *),
+            &[None, None, None, Some(Field::from("example.com"))]
+        ),
+        Err(Error::RecordMismatch { .. })
+    ));
+}
+
+#[test]
+fn _4() {
+    for (definition, reason) in [
+        (
+            "perm: u8 len 1..3",
+            ParseError::IllegalConstraint {
+                got: "len 1..3".to_string(),
+            },
+        ),
+        (
+            "perm: u8=0 1..",
+            ParseError::ConstraintViolation {
+                column: 0,
+                constraint: Box::new(Constraint::Range(
+                    Bound::Included(Field::U8(1)),
+                    Bound::Unbounded,
+                )),
+                value: "0".to_string(),
+            },
+        ),
+        (
+            "perm: istr in read, write}",
+            ParseError::IllegalConstraint {
+                got: "in read, write}".to_string(),
+            },
+        ),
+    ] {
+        assert!(matches!(
+            load(definition),
+            Err(Error::ParseError { reason: got, .. }) if got == reason
+        ));
+    }
+
+    let glob = Constraint::Glob("a*b?c".to_string());
+    assert!(glob.check(&Field::from("abxc")));
+    assert!(glob.check(&Field::from("axxbbyc")));
+    assert!(!glob.check(&Field::from("abc")));
+    assert!(glob.check(&Field::Null));
+}
@@ -0,0 +1,138 @@
+use crate::{filter, Document, Error, Field, ParseError, Signature};
+
+use super::load;
+
+const CONTENT: &str = r#"
+whitelist:  name:ustr   expiry:date     seen:datetime                   session:duration
+whitelist   joe         2025-06-30      "2024-05-01T10:00:00Z"          12h
+whitelist   bob         2024-12-31      "2024-05-01T12:30:00+08:00"     30d
+whitelist   carol       2024-02-29      "2024-04-30T23:59:59.5-02:00"   1h30m
+"#;
+
+fn names(doc: &Document, query: &str) -> Vec<String> {
+    let mut found = doc
+        .query(query)
+        .unwrap()
+        .into_iter()
+        .map(|r| r.args()[0].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    found.sort();
+    found
+}
+
+#[test]
+fn _1() {
+    let doc = load(CONTENT).unwrap();
+    let carol = doc.find(filter!("whitelist"; "carol", *, *, *)).unwrap();
+    assert_eq!(carol[0].args()[1], Field::Date(19782));
+    assert_eq!(carol[0].args()[1].as_timestamp(), Some(1709164800));
+    assert_eq!(
+        carol[0].args()[2],
+        Field::DateTime {
+            secs: 1714528799,
+            nanos: 500_000_000,
+            offset: -120
+        }
+    );
+    assert_eq!(carol[0].args()[3].as_duration(), Some(5400));
+    assert_eq!(
+        carol[0].to_rules_string(),
+        r#"whitelist carol 2024-02-29 "2024-04-30T23\:59\:59.5-02\:00" 1h30m"#
+    );
+
+    let copy = load(&doc.to_rules_string()).unwrap();
+    let args = |doc: &Document| {
+        doc.find(filter!("whitelist"; *, *, *, *))
+            .unwrap()
+            .into_iter()
+            .map(|r| r.args().to_vec())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(args(&copy), args(&doc));
+}
+
+#[test]
+fn _2() {
+    let doc = load(CONTENT).unwrap();
+    assert_eq!(
+        names(&doc, "whitelist expiry < 2025-01-01"),
+        vec!["bob", "carol"]
+    );
+    assert_eq!(names(&doc, "whitelist session >= 1d"), vec!["bob"]);
+    assert_eq!(names(&doc, "whitelist session < 2h"), vec!["carol"]);
+    assert_eq!(
+        names(&doc, "whitelist seen = \"2024-05-01T04:30:00Z\""),
+        vec!["bob"]
+    );
+    assert_eq!(
+        doc.find(filter!("whitelist"; *, *, >= "2024-05-01T03:00:00Z", *))
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let mut seen = doc
+        .find(filter!("whitelist"; *, *, *, *))
+        .unwrap()
+        .into_iter()
+        .map(|r| r.args()[0].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    seen.sort_by_key(|name| {
+        doc.find(filter!("whitelist"; name.as_str(), *, *, *))
+            .unwrap()[0]
+            .args()[2]
+            .clone()
+    });
+    assert_eq!(seen, vec!["carol", "bob", "joe"]);
+}
+
+#[test]
+fn _3() {
+    for (content, target) in [
+        ("t: date\nt 2023-02-29", Signature::Date),
+        ("t: date\nt 2024-13-01", Signature::Date),
+        ("t: date\nt 24-01-01", Signature::Date),
+        (
+            "t: datetime\nt \"2024-01-01T10:00:00\"",
+            Signature::DateTime,
+        ),
+        (
+            "t: datetime\nt \"2024-01-01T24:00:00Z\"",
+            Signature::DateTime,
+        ),
+        ("t: duration\nt 30", Signature::Duration),
+        ("t: duration\nt 30m1h", Signature::Duration),
+        ("t: duration\nt 3y", Signature::Duration),
+    ] {
+        assert!(matches!(
+            load(content),
+            Err(Error::ParseError {
+                line: 2,
+                reason: ParseError::TypeError { target: got, .. },
+                ..
+            }) if got == target
+        ));
+    }
+}
+
+#[test]
+fn _4() {
+    let doc = load("t: date datetime duration\nt 1969-12-31 1900-01-01 -1w2d").unwrap();
+    let found = doc.find(filter!("t"; *, *, *)).unwrap();
+    assert_eq!(found[0].args()[0], Field::Date(-1));
+    assert_eq!(found[0].args()[0].to_string(), "1969-12-31");
+    assert_eq!(found[0].args()[1].to_string(), "1900-01-01T00:00:00Z");
+    assert_eq!(found[0].args()[2], Field::Duration(-9 * 86400));
+    assert_eq!(found[0].args()[2].to_string(), "-9d");
+    assert_eq!(Field::Duration(0).to_string(), "0s");
+
+    assert_eq!(
+        Signature::DateTime.cast(&Field::Date(1)),
+        Some(Field::DateTime {
+            secs: 86400,
+            nanos: 0,
+            offset: 0
+        })
+    );
+    assert_eq!(Signature::Date.cast(&Field::from("2024-02-30")), None);
+}
@@ -0,0 +1,97 @@
+use crate::{filter, Error, Field, ParseError, Signature};
+
+use super::{doc, load};
+
+const CONTENT: &str = r#"
+whitelist:  name:ustr   ip:istr=127.0.0.1   expiry:u64?=null    level:u8=1
+whitelist   joe         127.0.0.2           123456              3
+whitelist   bob         127.0.0.3           100
+whitelist   alice
+"#;
+
+#[test]
+fn _1() {
+    let doc = doc(CONTENT);
+    let schema = doc.schema("whitelist").unwrap();
+    assert_eq!(schema.columns()[0].default(), None);
+    assert_eq!(
+        schema.columns()[1].default(),
+        Some(&Field::from("127.0.0.1"))
+    );
+    assert_eq!(schema.columns()[2].default(), Some(&Field::Null));
+    assert_eq!(schema.columns()[3].default(), Some(&Field::U8(1)));
+    assert_eq!(
+        schema.to_rules_string(),
+        "whitelist: name:ustr ip:istr=127.0.0.1 expiry:u64?=null level:u8=1"
+    );
+
+    let found = doc.find(filter!("whitelist"; *, *, *, *)).unwrap();
+    assert_eq!(
+        found
+            .iter()
+            .map(|r| r.to_rules_string())
+            .collect::<Vec<_>>(),
+        vec![
+            "whitelist joe 127.0.0.2 123456 3",
+            "whitelist bob 127.0.0.3 100 1",
+            "whitelist alice 127.0.0.1 null 1",
+        ]
+    );
+}
+
+#[test]
+fn _2() {
+    let mut doc = doc(CONTENT);
+    doc.insert(
+        "whitelist",
+        vec![Field::from("carol"), Field::from("127.0.0.9")],
+    )
+    .unwrap();
+    assert_eq!(
+        doc.find_one(filter!("whitelist"; "carol", *, *, *))
+            .unwrap()
+            .unwrap()
+            .args()[2..],
+        [Field::Null, Field::U8(1)]
+    );
+    assert!(matches!(
+        doc.insert("whitelist", vec![]),
+        Err(Error::RecordMismatch { .. })
+    ));
+}
+
+#[test]
+fn _3() {
+    for (content, reason) in [
+        (
+            "whitelist: ustr u64=0 istr\nwhitelist joe",
+            ParseError::ArgumentLengthMismatch {
+                expected: 3,
+                got: 1,
+            },
+        ),
+        (
+            "whitelist: ustr u8=-1",
+            ParseError::TypeError {
+                target: Signature::U8,
+                value: "-1".to_string(),
+                reason: String::new(),
+            },
+        ),
+    ] {
+        match load(content) {
+            Err(Error::ParseError { reason: got, .. }) => match (got, reason) {
+                (
+                    ParseError::TypeError { target, value, .. },
+                    ParseError::TypeError {
+                        target: expected_target,
+                        value: expected_value,
+                        ..
+                    },
+                ) => assert_eq!((target, value), (expected_target, expected_value)),
+                (got, reason) => assert_eq!(got, reason),
+            },
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{filter, Error, Field};
+
+use super::doc;
+
+const CONTENT: &str = r#"
+whitelist:  ustr    istr        u16     bool
+whitelist   joe     127.0.0.1   100     true
+whitelist   bob     127.0.0.1   200     false
+whitelist   Joe     127.0.0.3   300     true
+whitelist   carol   127.0.0.4   100     true
+"#;
+
+fn ustr(s: &str) -> Field {
+    Field::UStr {
+        original: s.to_string(),
+        lower: s.to_lowercase(),
+    }
+}
+
+#[test]
+fn _1() {
+    let doc = doc(CONTENT);
+    assert_eq!(
+        doc.project(filter!("whitelist"; *, "127.0.0.1", *, *), &[2, 0]),
+        Ok(vec![
+            vec![Field::U16(100), ustr("joe")],
+            vec![Field::U16(200), ustr("bob")],
+        ])
+    );
+    assert_eq!(
+        doc.project(
+            filter!("whitelist"; *, *, *, *)
+                .with_sort(2, true)
+                .with_limit(1),
+            &[1]
+        ),
+        Ok(vec![vec![Field::from("127.0.0.3")]])
+    );
+    assert!(matches!(
+        doc.project(filter!("whitelist"; *, *, *, *), &[4]),
+        Err(Error::UnknownColumn { .. })
+    ));
+}
+
+#[test]
+fn _2() {
+    let doc = doc(CONTENT);
+    // read from the record tree
+    assert_eq!(
+        doc.distinct(filter!("whitelist"; *, *, *, *), &[0]),
+        Ok(vec![
+            vec![ustr("bob")],
+            vec![ustr("carol")],
+            vec![ustr("joe")]
+        ])
+    );
+    assert_eq!(
+        doc.distinct(filter!("whitelist"; "JOE", *, *, *), &[0, 1]),
+        Ok(vec![
+            vec![ustr("joe"), Field::from("127.0.0.1")],
+            vec![ustr("joe"), Field::from("127.0.0.3")],
+        ])
+    );
+    assert_eq!(
+        doc.distinct(
+            filter!("whitelist"; *, *, *, *)
+                .with_offset(1)
+                .with_limit(1),
+            &[0]
+        ),
+        Ok(vec![vec![ustr("carol")]])
+    );
+    assert_eq!(
+        doc.distinct(filter!("whitelist"; *, *, < 300, *), &[2, 3]),
+        Ok(vec![
+            vec![Field::U16(100), Field::Bool(true)],
+            vec![Field::U16(200), Field::Bool(false)],
+        ])
+    );
+}
+
+#[test]
+fn _3() {
+    let doc = doc(CONTENT);
+    for columns in [&[0][..], &[1], &[0, 1], &[3], &[2, 0]] {
+        for filter in [
+            filter!("whitelist"; *, *, *, *),
+            filter!("whitelist"; *, "127.0.0.1", *, *),
+            filter!("whitelist"; *, *, >= 200, *),
+        ] {
+            let mut expected = doc.project(filter.clone(), columns).unwrap();
+            expected.sort();
+            expected.dedup();
+            assert_eq!(doc.distinct(filter, columns), Ok(expected));
+        }
+    }
+}
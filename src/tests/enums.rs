@@ -0,0 +1,122 @@
+use crate::{filter, Document, Error, Field, ParseError, Signature};
+
+use super::load;
+
+const CONTENT: &str = r#"
+enum level = guest member admin
+user:   name:ustr   level:level
+user    joe         admin
+user    bob         guest
+user    alice       member
+"#;
+
+fn names(doc: &Document, query: &str) -> Vec<String> {
+    let mut found = doc
+        .query(query)
+        .unwrap()
+        .into_iter()
+        .map(|r| r.args()[0].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    found.sort();
+    found
+}
+
+#[test]
+fn _1() {
+    let doc = load(CONTENT).unwrap();
+    let level = doc.enum_def("level").unwrap();
+    assert_eq!(level.variants(), &["guest", "member", "admin"]);
+    assert_eq!(
+        doc.schema("user").unwrap().to_rules_string(),
+        "user: name:ustr level:level"
+    );
+
+    let found = doc.find(filter!("user"; "joe", *)).unwrap();
+    assert_eq!(found[0].args()[1].as_variant(), Some("admin"));
+    assert_eq!(found[0].args()[1].to_string(), "admin");
+    assert!(matches!(&found[0].args()[1], Field::Enum(_, 2)));
+}
+
+#[test]
+fn _2() {
+    let doc = load(CONTENT).unwrap();
+    assert_eq!(names(&doc, "user level >= member"), vec!["alice", "joe"]);
+    assert_eq!(names(&doc, "user 1<admin"), vec!["alice", "bob"]);
+    assert_eq!(names(&doc, "user 1=guest"), vec!["bob"]);
+    assert_eq!(doc.find(filter!("user"; *, > "guest")).unwrap().len(), 2);
+
+    let found = doc.find(filter!("user"; *, *)).unwrap();
+    let mut levels = found
+        .iter()
+        .map(|r| r.args()[1].clone())
+        .collect::<Vec<_>>();
+    levels.sort();
+    assert_eq!(
+        levels.iter().map(Field::to_string).collect::<Vec<_>>(),
+        vec!["guest", "member", "admin"]
+    );
+}
+
+#[test]
+fn _3() {
+    assert!(matches!(
+        load("enum level = guest member\nuser: ustr level\nuser joe root"),
+        Err(Error::ParseError {
+            line: 3,
+            reason: ParseError::TypeError {
+                target: Signature::Enum(_),
+                ..
+            },
+            ..
+        })
+    ));
+    assert!(matches!(
+        load("enum level = guest guest"),
+        Err(Error::ParseError {
+            reason: ParseError::DuplicatedVariant { .. },
+            ..
+        })
+    ));
+    assert!(matches!(
+        load("enum level = guest\nenum level = admin"),
+        Err(Error::ParseError {
+            line: 2,
+            reason: ParseError::DuplicatedDefinition { .. },
+            ..
+        })
+    ));
+    assert!(matches!(
+        load("enum u8 = a b"),
+        Err(Error::ParseError {
+            reason: ParseError::IllegalName { .. },
+            ..
+        })
+    ));
+    assert!(matches!(
+        load("enum level guest"),
+        Err(Error::ParseError {
+            reason: ParseError::IllegalArgument,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn _4() {
+    let mut doc = load(CONTENT).unwrap();
+    doc.insert("user", vec![Field::from("carol"), Field::from("member")])
+        .unwrap();
+    assert!(matches!(
+        doc.insert("user", vec![Field::from("dave"), Field::from("root")]),
+        Err(Error::RecordMismatch { .. })
+    ));
+
+    let written = doc.to_rules_string();
+    assert!(written.starts_with("enum level = guest member admin\n"));
+    let copy = load(&written).unwrap();
+    assert_eq!(names(&copy, "user * member"), vec!["alice", "carol"]);
+
+    let rewritten = doc.rewrite().unwrap();
+    assert!(rewritten[0].1.contains("enum level = guest member admin"));
+    assert!(rewritten[0].1.contains("carol"));
+}
@@ -0,0 +1,77 @@
+use crate::{filter, Document, Error, Field, Filter, Predicate};
+
+use super::doc;
+
+const CONTENT: &str = r#"
+whitelist:  ustr    istr        u16     char    f32     bool
+whitelist   joe     127.0.0.1   1000    a       0.5     true
+whitelist   bob     127.0.0.1   2000    b       -1      false
+whitelist   Alice   127.0.0.3   3000    c       2.5     true
+"#;
+
+fn count(doc: &Document, filter: Filter) -> usize {
+    doc.find(filter).unwrap().len()
+}
+
+#[test]
+fn _1() {
+    assert_eq!(
+        filter!("whitelist"; *, "127.0.0.1", >= 5u8),
+        Filter::with_predicates(
+            "whitelist".to_string(),
+            vec![
+                Predicate::Any,
+                Predicate::Eq(Field::IStr("127.0.0.1".to_string())),
+                Predicate::Ge(Field::U64(5)),
+            ]
+        )
+    );
+    assert_eq!(
+        filter!(String::from("user"); != 'a', < -1, <= 2.0 * 2.0, > true, *),
+        Filter::with_predicates(
+            "user".to_string(),
+            vec![
+                Predicate::Ne(Field::Char('a')),
+                Predicate::Lt(Field::I64(-1)),
+                Predicate::Le(Field::F64(4.0)),
+                Predicate::Gt(Field::Bool(true)),
+                Predicate::Any,
+            ]
+        )
+    );
+}
+
+#[test]
+fn _2() {
+    let doc = doc(CONTENT);
+    assert_eq!(
+        count(&doc, filter!("whitelist"; *, "127.0.0.1", *, *, *, *)),
+        2
+    );
+    assert_eq!(count(&doc, filter!("whitelist"; "ALICE", *, *, *, *, *)), 1);
+    assert_eq!(count(&doc, filter!("whitelist"; *, *, > 1000, *, *, *)), 2);
+    assert_eq!(count(&doc, filter!("whitelist"; *, *, *, <= 'b', *, *)), 2);
+    assert_eq!(count(&doc, filter!("whitelist"; *, *, *, *, 2.5, true)), 1);
+
+    let ip = String::from("127.0.0.3");
+    assert_eq!(
+        count(&doc, filter!("whitelist"; *, ip.as_str(), *, *, *, *)),
+        1
+    );
+}
+
+#[test]
+fn _3() {
+    let doc = doc(CONTENT);
+    for filter in [
+        filter!("whitelist"; *, *, 70000, *, *, *),
+        filter!("whitelist"; *, *, -1, *, *, *),
+        filter!("whitelist"; *, *, *, "a", *, *),
+        filter!("whitelist"; *, *),
+    ] {
+        assert!(matches!(
+            doc.find(filter),
+            Err(Error::FilterMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{filter, Document, Error, Field, Filter, Plan};
+
+use super::doc;
+
+const CONTENT: &str = r#"
+whitelist:  ustr    istr indexed    u64
+whitelist   joe     127.0.0.1       123456
+whitelist   bob     127.0.0.1       100
+whitelist   Alice   127.0.0.3       123459
+whitelist   carol   127.0.0.4       0
+whitelist   bob     127.0.0.5       123457
+"#;
+
+fn names(doc: &Document, filter: Filter) -> Vec<String> {
+    let mut found = doc
+        .find(filter)
+        .unwrap()
+        .into_iter()
+        .map(|r| r.args()[0].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    found.sort();
+    found
+}
+
+#[test]
+fn _1() {
+    let doc = doc(CONTENT);
+    assert!(doc.schema("whitelist").unwrap().columns()[1].indexed());
+    assert_eq!(
+        doc.schema("whitelist").unwrap().to_rules_string(),
+        "whitelist: ustr istr indexed u64"
+    );
+
+    let by_ip = filter!("whitelist"; *, "127.0.0.1", *);
+    assert_eq!(
+        doc.explain(by_ip.clone()).unwrap(),
+        Plan::Index {
+            column: 1,
+            estimate: 2
+        }
+    );
+    assert_eq!(names(&doc, by_ip), vec!["bob", "joe"]);
+
+    let by_name = filter!("whitelist"; "joe", *, *);
+    assert_eq!(doc.explain(by_name).unwrap(), Plan::Tree { estimate: 2 });
+    assert_eq!(
+        doc.explain(filter!("whitelist"; *, *, > 5)).unwrap(),
+        Plan::Tree { estimate: 5 }
+    );
+}
+
+#[test]
+fn _2() {
+    let doc = doc(CONTENT);
+    let filter = doc.query("whitelist (1=127.0.0.1 or 1=127.0.0.5) and 2>=1000");
+    let mut found = filter
+        .unwrap()
+        .into_iter()
+        .map(|r| r.args()[2].clone())
+        .collect::<Vec<_>>();
+    found.sort();
+    assert_eq!(found, vec![Field::U64(123456), Field::U64(123457)]);
+
+    let filter = filter!("whitelist"; *, *, *).and(crate::Condition::field(
+        1,
+        crate::Predicate::Eq(Field::from("127.0.0.4")),
+    ));
+    assert_eq!(doc.explain(filter.clone()).unwrap().estimate(), 1);
+    assert_eq!(names(&doc, filter), vec!["carol"]);
+}
+
+#[test]
+fn _3() {
+    let mut doc = doc(CONTENT);
+    doc.insert(
+        "whitelist",
+        vec![Field::from("zoe"), Field::from("127.0.0.1"), Field::U64(1)],
+    )
+    .unwrap();
+    doc.remove(filter!("whitelist"; "joe", *, *)).unwrap();
+    doc.update(
+        filter!("whitelist"; *, "127.0.0.5", *),
+        &[None, Some(Field::from("127.0.0.1")), None],
+    )
+    .unwrap();
+
+    let by_ip = filter!("whitelist"; *, "127.0.0.1", *);
+    assert_eq!(doc.explain(by_ip.clone()).unwrap().estimate(), 3);
+    assert_eq!(names(&doc, by_ip), vec!["bob", "bob", "zoe"]);
+    assert!(names(&doc, filter!("whitelist"; *, "127.0.0.5", *)).is_empty());
+    assert_eq!(doc.get("whitelist").unwrap().len(), 5);
+
+    assert!(matches!(
+        doc.update(
+            filter!("whitelist"; "zoe", *, *),
+            &[Some(Field::from("bob")), None, Some(Field::U64(100))],
+        ),
+        Err(Error::DuplicatedEntry { .. })
+    ));
+    assert_eq!(
+        names(&doc, filter!("whitelist"; *, "127.0.0.1", *)),
+        vec!["bob", "bob", "zoe"]
+    );
+}
+
+#[test]
+fn _4() {
+    let mut doc = doc(CONTENT);
+    let by_expiry = filter!("whitelist"; *, *, 0);
+    assert_eq!(
+        doc.explain(by_expiry.clone()).unwrap(),
+        Plan::Tree { estimate: 5 }
+    );
+
+    doc.create_index("whitelist", 2).unwrap();
+    assert_eq!(
+        doc.explain(by_expiry.clone()).unwrap(),
+        Plan::Index {
+            column: 2,
+            estimate: 1
+        }
+    );
+    assert_eq!(names(&doc, by_expiry.clone()), vec!["carol"]);
+    assert_eq!(
+        doc.to_rules_string().lines().next().unwrap(),
+        "whitelist: ustr  istr indexed u64 indexed"
+    );
+
+    doc.drop_index("whitelist", 2).unwrap();
+    doc.drop_index("whitelist", 1).unwrap();
+    assert_eq!(doc.explain(by_expiry).unwrap(), Plan::Tree { estimate: 5 });
+    assert!(matches!(
+        doc.create_index("whitelist", 3),
+        Err(Error::UnknownColumn { .. })
+    ));
+    assert!(matches!(
+        doc.create_index("blacklist", 0),
+        Err(Error::NoDefinition { .. })
+    ));
+}
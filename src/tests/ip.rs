@@ -0,0 +1,161 @@
+use std::net::IpAddr;
+
+use crate::{filter, Document, Error, Field, ParseError, Plan, Signature};
+
+use super::load;
+
+const CONTENT: &str = r#"
+whitelist:  name:ustr   net:cidr        addr:ip
+whitelist   joe         10.0.0.0/16     10.0.0.1
+whitelist   bob         10.0.3.0/24     "::1"
+whitelist   carol       0.0.0.0/0       192.168.1.1
+whitelist   dave        "2001:db8::/32" "2001:db8::1"
+whitelist   erin        10.1.2.3        10.1.2.3
+"#;
+
+fn names(doc: &Document, query: &str) -> Vec<String> {
+    let mut found = doc
+        .query(query)
+        .unwrap()
+        .into_iter()
+        .map(|r| r.args()[0].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    found.sort();
+    found
+}
+
+fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+#[test]
+fn _1() {
+    let doc = load(CONTENT).unwrap();
+    assert_eq!(
+        names(&doc, "whitelist * 10.0.3.7 *"),
+        vec!["bob", "carol", "joe"]
+    );
+    assert_eq!(names(&doc, "whitelist * 10.1.2.3 *"), vec!["carol", "erin"]);
+    assert_eq!(names(&doc, "whitelist net=10.0.0.0/16"), vec!["joe"]);
+    assert_eq!(
+        names(&doc, "whitelist net has 10.0.3.0/25"),
+        vec!["bob", "carol", "joe"]
+    );
+    assert_eq!(names(&doc, "whitelist net has 2001:db8::5"), vec!["dave"]);
+    assert_eq!(names(&doc, "whitelist addr=::1"), vec!["bob"]);
+    assert_eq!(
+        doc.find(filter!("whitelist"; *, "10.0.200.1", *))
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(
+        doc.find(filter!("whitelist"; *, *, ip("10.0.0.1")))
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn _2() {
+    let doc = load(CONTENT).unwrap();
+    let erin = doc.find(filter!("whitelist"; "erin", *, *)).unwrap();
+    assert_eq!(erin[0].args()[1], Field::Cidr(ip("10.1.2.3"), 32));
+    assert_eq!(erin[0].args()[2].as_ip(), Some(ip("10.1.2.3")));
+
+    let dave = doc.find(filter!("whitelist"; "dave", *, *)).unwrap();
+    assert_eq!(
+        dave[0].to_rules_string(),
+        r#"whitelist dave "2001\:db8\:\:/32" "2001\:db8\:\:1""#
+    );
+    let copy = load(&doc.to_rules_string()).unwrap();
+    assert_eq!(
+        names(&copy, "whitelist * 10.0.3.7 *"),
+        vec!["bob", "carol", "joe"]
+    );
+}
+
+#[test]
+fn _3() {
+    for (content, target) in [
+        ("t: ipv4\nt \"::1\"", Signature::Ipv4),
+        ("t: ipv6\nt 1.2.3.4", Signature::Ipv6),
+        ("t: ip\nt 1.2.3", Signature::Ip),
+        ("t: cidr\nt 10.0.0.1/16", Signature::Cidr),
+        ("t: cidr\nt 10.0.0.0/33", Signature::Cidr),
+    ] {
+        assert!(matches!(
+            load(content),
+            Err(Error::ParseError {
+                line: 2,
+                reason: ParseError::TypeError { target: got, .. },
+                ..
+            }) if got == target
+        ));
+    }
+}
+
+#[test]
+fn _4() {
+    let mut doc = load(
+        r#"
+route:  net:cidr    gateway:ipv4
+route   10.0.0.0/8  10.0.0.1
+route   10.2.0.0/16 10.2.0.1
+route   10.2.3.0/24 10.2.3.1
+route   10.3.0.0/16 10.3.0.1
+"#,
+    )
+    .unwrap();
+    let gateways = |doc: &Document, addr: &str| {
+        doc.find(filter!("route"; addr, *))
+            .unwrap()
+            .into_iter()
+            .map(|r| r.args()[1].to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        gateways(&doc, "10.2.3.4"),
+        vec!["10.0.0.1", "10.2.0.1", "10.2.3.1"]
+    );
+    assert_eq!(gateways(&doc, "10.3.3.4"), vec!["10.0.0.1", "10.3.0.1"]);
+    assert!(gateways(&doc, "11.0.0.1").is_empty());
+
+    doc.remove(filter!("route"; "10.2.0.0/16", *)).unwrap();
+    assert_eq!(gateways(&doc, "10.2.3.4"), vec!["10.0.0.1", "10.2.3.1"]);
+
+    doc.create_index("route", 0).unwrap();
+    assert!(matches!(
+        doc.explain(filter!("route"; "10.2.3.4", *)),
+        Ok(Plan::Index {
+            column: 0,
+            estimate: 2
+        })
+    ));
+    assert_eq!(gateways(&doc, "10.2.3.4"), vec!["10.0.0.1", "10.2.3.1"]);
+}
+
+#[test]
+fn _5() {
+    let mut doc = load(
+        r#"
+host:   name:ustr   net:cidr?
+host    joe         10.0.0.0/8
+host    bob         null
+host    carol       10.1.0.0/16
+"#,
+    )
+    .unwrap();
+    assert_eq!(names(&doc, "host net=null"), vec!["bob"]);
+    assert_eq!(names(&doc, "host net!=null"), vec!["carol", "joe"]);
+    assert_eq!(names(&doc, "host * 10.1.2.3"), vec!["carol", "joe"]);
+    assert_eq!(doc.find(filter!("host"; *, *)).unwrap().len(), 3);
+
+    doc.remove(filter!("host"; *, Field::Null)).unwrap();
+    assert!(names(&doc, "host net=null").is_empty());
+    doc.insert("host", vec![Field::from("dave"), Field::Null])
+        .unwrap();
+    assert_eq!(names(&doc, "host net=null"), vec!["dave"]);
+}
@@ -0,0 +1,143 @@
+use crate::{filter, Condition, Document, Error, Field, Filter, Predicate, Record};
+
+use super::load;
+
+fn doc() -> Document {
+    let mut content = String::from("user: ustr u16 indexed u8\n");
+    for i in 0..200 {
+        content.push_str(&format!("user u{} {} {}\n", i % 20, i, i % 7));
+    }
+    load(&content).unwrap()
+}
+
+fn lines<'a>(found: impl Iterator<Item = &'a Record>) -> Vec<u32> {
+    let mut lines = found.map(|r| r.line()).collect::<Vec<_>>();
+    lines.sort();
+    lines
+}
+
+#[test]
+fn _1() {
+    let doc = doc();
+    for filter in [
+        filter!("user"; *, *, *),
+        filter!("user"; "U3", *, *),
+        filter!("user"; *, 42, *),
+        filter!("user"; *, >= 150, < 3),
+        filter!("user"; *, *, 6).or(Condition::field(0, Predicate::Eq(Field::from("u1")))),
+        filter!("user"; *, *, *).and(!Condition::field(2, Predicate::Gt(Field::U8(0)))),
+        filter!("user"; *, *, *).and(Condition::field(
+            1,
+            Predicate::Eq(Field::U16(7)).or(Predicate::Eq(Field::U16(9))),
+        )),
+    ] {
+        let found = doc.find(filter.clone()).unwrap();
+        assert!(!found.is_empty());
+        assert_eq!(
+            lines(doc.iter_matches(filter).unwrap()),
+            lines(found.into_iter())
+        );
+    }
+}
+
+#[test]
+fn _2() {
+    let doc = doc();
+    let all = || filter!("user"; *, *, *);
+
+    assert_eq!(doc.iter_matches(all()).unwrap().count(), 200);
+    assert_eq!(doc.iter_matches(all()).unwrap().take(3).count(), 3);
+    assert!(doc
+        .iter_matches(all())
+        .unwrap()
+        .any(|r| r.args()[1] == Field::U16(199)));
+    assert_eq!(
+        doc.iter_matches(all().with_offset(190).with_limit(20))
+            .unwrap()
+            .count(),
+        10
+    );
+    assert_eq!(
+        doc.iter_matches(filter!("user"; *, *, 0).with_limit(5))
+            .unwrap()
+            .count(),
+        5
+    );
+
+    let sorted = |filter: Filter| {
+        doc.iter_matches(filter)
+            .unwrap()
+            .map(|r| r.args()[1].clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        sorted(all().with_sort(1, true).with_offset(1).with_limit(2)),
+        vec![Field::U16(198), Field::U16(197)]
+    );
+    assert!(matches!(
+        doc.iter_matches(filter!("user"; *, *)),
+        Err(Error::FilterMismatch { .. })
+    ));
+}
+
+#[test]
+fn _3() {
+    let doc = doc();
+    let lines = |found: Vec<&Record>| found.into_iter().map(Record::line).collect::<Vec<_>>();
+
+    for filter in [
+        filter!("user"; *, *, *),
+        filter!("user"; *, *, 3),
+        filter!("user"; *, >= 150, *),
+        filter!("user"; *, *, *).and(Condition::field(
+            1,
+            Predicate::Eq(Field::U16(90)).or(Predicate::Eq(Field::U16(9))),
+        )),
+    ] {
+        for (offset, limit) in [(0, 3), (2, 5), (10, 100)] {
+            let paged = filter.clone().with_offset(offset).with_limit(limit);
+            let found = doc.find(paged.clone()).unwrap();
+            assert!(offset > 0 || !found.is_empty());
+            assert_eq!(
+                lines(doc.iter_matches(paged.clone()).unwrap().collect()),
+                lines(found)
+            );
+        }
+    }
+
+    let first = doc
+        .iter_matches(filter!("user"; *, *, *).with_limit(3))
+        .unwrap()
+        .map(|r| r.args()[1].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(first, vec![Field::U16(0), Field::U16(1), Field::U16(2)]);
+    assert_eq!(
+        doc.count(filter!("user"; *, *, *).with_offset(195))
+            .unwrap(),
+        5
+    );
+}
+
+#[test]
+fn _4() {
+    let doc = doc();
+
+    // the first match is the second record, later records are left unvisited
+    let mut found = doc.iter_matches(filter!("user"; *, *, 1)).unwrap();
+    assert_eq!(found.next().unwrap().args()[1], Field::U16(1));
+    assert_eq!(found.size_hint(), (0, Some(198)));
+
+    let mut found = doc
+        .iter_matches(
+            filter!("user"; *, *, *).and(Condition::field(0, Predicate::Ne(Field::from("u0")))),
+        )
+        .unwrap();
+    assert_eq!(found.by_ref().take(1).count(), 1);
+    assert_eq!(found.size_hint(), (0, Some(198)));
+    assert_eq!(
+        doc.iter_matches(filter!("user"; *, *, *).with_limit(2))
+            .unwrap()
+            .size_hint(),
+        (0, Some(2))
+    );
+}
@@ -0,0 +1,106 @@
+use crate::{filter, Error, Record};
+
+use super::doc;
+
+const CONTENT: &str = r#"
+user:   ustr    u8      u16
+perm:   ustr    istr    bool    u32
+
+user    joe     3       1
+user    bob     1       2
+user    Alice   3       3
+
+perm    alice   read    true    1
+perm    joe     write   true    3
+perm    JOE     read    true    2
+perm    bob     write   false   1
+perm    carol   read    true    9
+"#;
+
+fn lines(pairs: Vec<(&Record, &Record)>) -> Vec<(u32, u32)> {
+    pairs
+        .into_iter()
+        .map(|(a, b)| (a.line(), b.line()))
+        .collect()
+}
+
+#[test]
+fn _1() {
+    let doc = doc(CONTENT);
+    // first column, looked up in the record tree
+    assert_eq!(
+        lines(
+            doc.join(filter!("user"; *, *, *), 0, filter!("perm"; *, *, *, *), 0)
+                .unwrap()
+        ),
+        vec![(5, 10), (5, 11), (6, 12), (7, 9)]
+    );
+    assert_eq!(
+        lines(
+            doc.join(
+                filter!("user"; *, 3, *),
+                0,
+                filter!("perm"; *, "read", *, *),
+                0
+            )
+            .unwrap()
+        ),
+        vec![(5, 11), (7, 9)]
+    );
+    assert_eq!(
+        lines(
+            doc.join(
+                filter!("user"; *, *, *).with_sort(2, true).with_limit(2),
+                0,
+                filter!("perm"; *, *, *, *),
+                0
+            )
+            .unwrap()
+        ),
+        vec![(7, 9), (6, 12)]
+    );
+}
+
+#[test]
+fn _2() {
+    let mut doc = doc(CONTENT);
+    // u16 joined with u32, hashed and then indexed
+    let expected = vec![(5, 9), (5, 12), (6, 11), (7, 10)];
+    assert_eq!(
+        lines(
+            doc.join(filter!("user"; *, *, *), 2, filter!("perm"; *, *, *, *), 3)
+                .unwrap()
+        ),
+        expected
+    );
+    doc.create_index("perm", 3).unwrap();
+    assert_eq!(
+        lines(
+            doc.join(filter!("user"; *, *, *), 2, filter!("perm"; *, *, *, *), 3)
+                .unwrap()
+        ),
+        expected
+    );
+    // strings never equal integers
+    assert!(doc
+        .join(filter!("user"; *, *, *), 0, filter!("perm"; *, *, *, *), 3)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn _3() {
+    let doc = doc(CONTENT);
+    assert!(matches!(
+        doc.join(filter!("user"; *, *, *), 3, filter!("perm"; *, *, *, *), 0),
+        Err(Error::UnknownColumn { .. })
+    ));
+    assert!(matches!(
+        doc.join(filter!("user"; *, *, *), 0, filter!("group"; *), 0),
+        Err(Error::NoDefinition { .. })
+    ));
+    assert!(matches!(
+        doc.join(filter!("user"; *, *, *), 0, filter!("perm"; *, *), 0),
+        Err(Error::FilterMismatch { .. })
+    ));
+}
@@ -0,0 +1,132 @@
+use crate::{filter, Document, Error, Field, ParseError, Plan, Signature};
+
+use super::load;
+
+const CONTENT: &str = r#"
+ports:  name:istr   ports:[u16] indexed
+ports   web         [80,443]
+ports   mail        "[25, 465, 587]"
+ports   none        []
+"#;
+
+fn names(doc: &Document, query: &str) -> Vec<String> {
+    let mut found = doc
+        .query(query)
+        .unwrap()
+        .into_iter()
+        .map(|r| r.args()[0].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    found.sort();
+    found
+}
+
+#[test]
+fn _1() {
+    let doc = load(CONTENT).unwrap();
+    let schema = doc.schema("ports").unwrap();
+    assert_eq!(schema.sig()[1], Signature::List(Box::new(Signature::U16)));
+    assert_eq!(
+        schema.to_rules_string(),
+        "ports: name:istr ports:[u16] indexed"
+    );
+
+    let mail = doc.find(filter!("ports"; "mail", *)).unwrap();
+    assert_eq!(
+        mail[0].args()[1],
+        Field::List(vec![Field::U16(25), Field::U16(465), Field::U16(587)])
+    );
+    assert_eq!(mail[0].to_rules_string(), "ports mail [25,465,587]");
+    assert_eq!(
+        doc.find(filter!("ports"; "none", *)).unwrap()[0].args()[1],
+        Field::List(Vec::new())
+    );
+}
+
+#[test]
+fn _2() {
+    let doc = load(CONTENT).unwrap();
+    assert_eq!(
+        doc.find(filter!("ports"; *, has 443)).unwrap()[0].args()[0],
+        Field::from("web")
+    );
+    assert_eq!(
+        names(&doc, "ports ports has 25 or ports has 80"),
+        vec!["mail", "web"]
+    );
+    assert_eq!(names(&doc, "ports * [80,443]"), vec!["web"]);
+    assert_eq!(names(&doc, "ports ports=[443,80]"), Vec::<String>::new());
+    assert_eq!(names(&doc, "ports not ports has 80"), vec!["mail", "none"]);
+
+    assert!(matches!(
+        doc.explain(filter!("ports"; *, has 443)),
+        Ok(Plan::Index {
+            column: 1,
+            estimate: 1
+        })
+    ));
+    assert!(matches!(
+        doc.find(filter!("ports"; has 443, *)),
+        Err(Error::FilterMismatch { .. })
+    ));
+}
+
+#[test]
+fn _3() {
+    let mut doc = load(CONTENT).unwrap();
+    doc.update(
+        filter!("ports"; "web", *),
+        &[None, Some(Field::List(vec![Field::U16(8080)]))],
+    )
+    .unwrap();
+    assert!(doc.find(filter!("ports"; *, has 443)).unwrap().is_empty());
+    assert_eq!(names(&doc, "ports ports has 8080"), vec!["web"]);
+
+    doc.remove(filter!("ports"; *, has 465)).unwrap();
+    assert!(doc.find(filter!("ports"; *, has 25)).unwrap().is_empty());
+    assert_eq!(doc.find(filter!("ports"; *, *)).unwrap().len(), 2);
+}
+
+#[test]
+fn _4() {
+    let mut doc = load("tags: istr [istr]").unwrap();
+    let tags = Field::List(vec![
+        Field::from("a,b"),
+        Field::from("c d"),
+        Field::from("back\\slash"),
+    ]);
+    doc.insert("tags", vec![Field::from("x"), tags.clone()])
+        .unwrap();
+
+    let copy = load(&doc.to_rules_string()).unwrap();
+    assert_eq!(
+        copy.find(filter!("tags"; "x", *)).unwrap()[0].args()[1],
+        tags
+    );
+
+    assert!(matches!(
+        load("ports: [u16]\nports 80"),
+        Err(Error::ParseError {
+            line: 2,
+            reason: ParseError::TypeError { .. },
+            ..
+        })
+    ));
+    assert!(matches!(
+        load("ports: [u16]\nports [80,http]"),
+        Err(Error::ParseError {
+            line: 2,
+            reason: ParseError::TypeError {
+                target: Signature::U16,
+                ..
+            },
+            ..
+        })
+    ));
+    assert!(matches!(
+        load("ports: [[u16]]"),
+        Err(Error::ParseError {
+            reason: ParseError::UnrecognisedType { .. },
+            ..
+        })
+    ));
+}
@@ -1,3 +1,42 @@
+use std::path::Path;
+
+use crate::{Document, Error};
+
+mod aggregate;
 mod clear_comment;
+mod condition;
+mod constraint;
+mod date;
+mod default;
+mod distinct;
+mod enums;
+mod filter_macro;
+mod index;
+mod ip;
+mod iter_matches;
+mod join;
+mod list;
+mod modify;
+mod named;
+mod null;
+mod order;
+mod paging;
+mod parse_filter;
+mod pattern;
+mod predicate;
+mod query;
+mod rewrite;
+mod round_trip;
 mod split_args;
 mod split_col;
+mod validity;
+
+/// Loads rules text as if it is read from `test.rules`.
+fn load(content: &str) -> Result<Document, Error> {
+    Document::load_as(content, Path::new("test.rules"))
+}
+
+/// Loads rules text that is known to be valid.
+fn doc(content: &str) -> Document {
+    load(content).unwrap()
+}
@@ -0,0 +1,163 @@
+use crate::{Error, Field, Filter, ParseError};
+
+use super::{doc, load};
+
+const CONTENT: &str = r#"
+user:   ustr    u8
+user    Bob     17
+user    John    25
+user    Alice   23
+"#;
+
+fn name(s: &str) -> Filter {
+    Filter::new(
+        "user".to_string(),
+        &[Some(Field::IStr(s.to_string())), None],
+    )
+}
+
+#[test]
+fn _1() {
+    let mut doc = doc(CONTENT);
+    doc.insert(
+        "user",
+        vec![Field::IStr("Sirius".to_string()), Field::U64(19)],
+    )
+    .unwrap();
+
+    let found = doc.find_one(name("sirius")).unwrap().unwrap();
+    assert_eq!(found.args()[1], Field::U8(19));
+    assert_eq!(found.location(), "test");
+}
+
+#[test]
+fn _2() {
+    let mut doc = doc(CONTENT);
+    let res = doc.insert("user", vec![Field::IStr("bob".to_string()), Field::U8(17)]);
+    assert_eq!(
+        res,
+        Err(Error::DuplicatedEntry {
+            first_appear: "test".to_string(),
+            line: 3,
+            label: "user".to_string()
+        })
+    );
+    assert_eq!(doc.find(name("bob")).unwrap()[0].line(), 3);
+}
+
+#[test]
+fn _3() {
+    let mut doc = doc(CONTENT);
+    assert!(matches!(
+        doc.insert(
+            "user",
+            vec![Field::IStr("Sirius".to_string()), Field::U64(300)]
+        ),
+        Err(Error::RecordMismatch { .. })
+    ));
+    assert!(matches!(
+        doc.insert("user", vec![Field::IStr("Sirius".to_string())]),
+        Err(Error::RecordMismatch { .. })
+    ));
+    assert_eq!(
+        doc.insert("group", vec![]),
+        Err(Error::NoDefinition {
+            label: "group".to_string()
+        })
+    );
+}
+
+#[test]
+fn _4() {
+    let mut doc = doc(CONTENT);
+    let removed = doc.remove(name("JOHN")).unwrap();
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].line(), 4);
+    assert_eq!(
+        doc.find(name("john")).unwrap(),
+        Vec::<&crate::Record>::new()
+    );
+    assert_eq!(
+        doc.find(Filter::new("user".to_string(), &[None, None]))
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn _5() {
+    let mut doc = doc(CONTENT);
+    let changed = doc
+        .update(name("alice"), &[None, Some(Field::U8(24))])
+        .unwrap();
+    assert_eq!(changed, 1);
+
+    let found = doc.find_one(name("alice")).unwrap().unwrap();
+    assert_eq!(found.args()[1], Field::U8(24));
+    assert_eq!(found.line(), 5);
+}
+
+#[test]
+fn _6() {
+    let mut doc = doc(CONTENT);
+    let res = doc.update(
+        name("alice"),
+        &[Some(Field::IStr("bob".to_string())), Some(Field::U8(17))],
+    );
+    assert_eq!(
+        res,
+        Err(Error::DuplicatedEntry {
+            first_appear: "test".to_string(),
+            line: 3,
+            label: "user".to_string()
+        })
+    );
+    assert_eq!(doc.find_one(name("alice")).unwrap().unwrap().line(), 5);
+}
+
+#[test]
+fn _7() {
+    let mut doc = doc(CONTENT);
+    assert!(matches!(
+        doc.update(name("alice"), &[None, Some(Field::Bool(true))]),
+        Err(Error::RecordMismatch { .. })
+    ));
+    assert!(matches!(
+        doc.update(name("alice"), &[None]),
+        Err(Error::RecordMismatch { .. })
+    ));
+    assert!(matches!(
+        doc.update(
+            name("alice"),
+            &[
+                Some(Field::from("alice")),
+                Some(Field::U8(24)),
+                Some(Field::U8(1)),
+                Some(Field::U8(2))
+            ]
+        ),
+        Err(Error::RecordMismatch { .. })
+    ));
+    assert_eq!(
+        doc.find_one(name("alice")).unwrap().unwrap().args()[1],
+        Field::U8(23)
+    );
+}
+
+#[test]
+fn _8() {
+    let res = load("user: ustr\nuser bob\nuser BOB\n");
+    assert_eq!(
+        res.unwrap_err(),
+        Error::ParseError {
+            location: "test".to_string(),
+            line: 3,
+            reason: ParseError::DuplicatedEntry {
+                first_appear: "test".to_string(),
+                line: 2,
+                label: "user".to_string()
+            }
+        }
+    );
+}
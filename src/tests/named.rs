@@ -0,0 +1,97 @@
+use crate::{Error, Field, ParseError, Predicate, Query};
+
+use super::{doc, load};
+
+const CONTENT: &str = r#"
+whitelist:  name:ustr   ip:istr indexed     expiry:u64
+whitelist   joe         127.0.0.1           123456
+whitelist   bob         127.0.0.1           100
+whitelist   alice       127.0.0.3           123459
+"#;
+
+#[test]
+fn _1() {
+    let doc = doc(CONTENT);
+    let schema = doc.schema("whitelist").unwrap();
+    assert_eq!(schema.position("ip"), Some(1));
+    assert_eq!(schema.position("user"), None);
+    assert_eq!(schema.columns()[2].name(), Some("expiry"));
+    assert_eq!(
+        schema.to_rules_string(),
+        "whitelist: name:ustr ip:istr indexed expiry:u64"
+    );
+
+    let found = doc
+        .find(doc.named_filter("whitelist", &[]).unwrap())
+        .unwrap();
+    assert_eq!(found[1].get("name").and_then(Field::as_str), Some("bob"));
+    assert_eq!(found[1].get("expiry"), Some(&Field::U64(100)));
+    assert_eq!(found[1].get("user"), None);
+}
+
+#[test]
+fn _2() {
+    let doc = doc(CONTENT);
+    let filter = doc
+        .named_filter(
+            "whitelist",
+            &[
+                ("ip", Predicate::Eq(Field::from("127.0.0.1"))),
+                ("expiry", Predicate::Gt(Field::U64(1000))),
+                ("expiry", Predicate::Lt(Field::U64(200000))),
+            ],
+        )
+        .unwrap();
+    let found = doc.find(filter).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].get("name").and_then(Field::as_str), Some("joe"));
+
+    assert!(matches!(
+        doc.named_filter("whitelist", &[("user", Predicate::Any)]),
+        Err(Error::UnknownColumn { .. })
+    ));
+}
+
+#[test]
+fn _3() {
+    let doc = doc(CONTENT);
+    let query = Query::parse(&doc, "whitelist ip=127.0.0.1 | sort expiry desc").unwrap();
+    assert_eq!(query.sort(), &[(2, true)]);
+    let found = query.run(&doc).unwrap();
+    assert_eq!(
+        found
+            .iter()
+            .map(|r| r.get("name").and_then(Field::as_str).unwrap())
+            .collect::<Vec<_>>(),
+        vec!["joe", "bob"]
+    );
+}
+
+#[test]
+fn _4() {
+    for (definition, reason) in [
+        (
+            "whitelist: name:ustr name:istr",
+            ParseError::DuplicatedColumn {
+                name: "name".to_string(),
+            },
+        ),
+        (
+            "whitelist: :ustr",
+            ParseError::IllegalColumnName {
+                name: String::new(),
+            },
+        ),
+        (
+            "whitelist: 0:ustr",
+            ParseError::IllegalColumnName {
+                name: "0".to_string(),
+            },
+        ),
+    ] {
+        assert!(matches!(
+            load(definition),
+            Err(Error::ParseError { reason: got, .. }) if got == reason
+        ));
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{filter, Aggregate, Error, Field, Predicate, Query, Signature};
+
+use super::{doc, load};
+
+const CONTENT: &str = r#"
+whitelist:  name:ustr   ip:istr     expiry:u64?
+whitelist   joe         127.0.0.1   123456
+whitelist   bob         127.0.0.1   null
+whitelist   alice       127.0.0.3   100
+whitelist   carol       127.0.0.4   null
+"#;
+
+fn names(found: Vec<&crate::Record>) -> Vec<&str> {
+    found
+        .into_iter()
+        .map(|r| r.get("name").and_then(Field::as_str).unwrap())
+        .collect()
+}
+
+#[test]
+fn _1() {
+    let doc = doc(CONTENT);
+    let schema = doc.schema("whitelist").unwrap();
+    assert!(schema.columns()[2].nullable());
+    assert!(!schema.columns()[1].nullable());
+    assert_eq!(
+        schema.to_rules_string(),
+        "whitelist: name:ustr ip:istr expiry:u64?"
+    );
+
+    let found = doc.find(filter!("whitelist"; "bob", *, *)).unwrap();
+    assert_eq!(found[0].get("expiry"), Some(&Field::Null));
+    assert_eq!(found[0].to_rules_string(), "whitelist bob 127.0.0.1 null");
+
+    assert!(matches!(
+        load("whitelist: ustr u64\nwhitelist joe null"),
+        Err(Error::ParseError { .. })
+    ));
+}
+
+#[test]
+fn _2() {
+    let doc = doc(CONTENT);
+    assert_eq!(
+        names(doc.find(filter!("whitelist"; *, *, *)).unwrap()),
+        vec!["joe", "bob", "alice", "carol"]
+    );
+    assert_eq!(
+        names(doc.find(filter!("whitelist"; *, *, null)).unwrap()),
+        vec!["bob", "carol"]
+    );
+    assert_eq!(
+        names(doc.find(filter!("whitelist"; *, *, != null)).unwrap()),
+        vec!["joe", "alice"]
+    );
+    // comparisons do not match null
+    assert_eq!(
+        names(doc.find(filter!("whitelist"; *, *, < 200000)).unwrap()),
+        vec!["joe", "alice"]
+    );
+    assert_eq!(
+        names(doc.find(filter!("whitelist"; *, *, != 100)).unwrap()),
+        vec!["joe", "bob", "carol"]
+    );
+    assert_eq!(
+        names(
+            doc.find(doc.parse_filter("whitelist", &["*", "*", "null"]).unwrap())
+                .unwrap()
+        ),
+        vec!["bob", "carol"]
+    );
+    assert_eq!(
+        names(doc.query("whitelist expiry!=null | sort expiry").unwrap()),
+        vec!["alice", "joe"]
+    );
+    assert_eq!(
+        Query::parse(&doc, "whitelist expiry=null")
+            .unwrap()
+            .filter()
+            .to_string(),
+        "whitelist 2=null"
+    );
+    // nulls come first
+    assert_eq!(
+        names(
+            doc.find(filter!("whitelist"; *, *, *).with_sort(2, false))
+                .unwrap()
+        ),
+        vec!["bob", "carol", "alice", "joe"]
+    );
+}
+
+#[test]
+fn _3() {
+    let mut doc = doc(CONTENT);
+    doc.create_index("whitelist", 2).unwrap();
+    assert_eq!(
+        names(doc.find(filter!("whitelist"; *, *, null)).unwrap()),
+        vec!["bob", "carol"]
+    );
+
+    assert_eq!(
+        doc.aggregate(
+            filter!("whitelist"; *, *, *),
+            &[
+                Aggregate::Count,
+                Aggregate::Sum(2),
+                Aggregate::Min(2),
+                Aggregate::Average(2)
+            ]
+        ),
+        Ok(vec![
+            Some(Field::U64(4)),
+            Some(Field::U64(123556)),
+            Some(Field::U64(100)),
+            Some(Field::F64(61778.0)),
+        ])
+    );
+
+    doc.insert(
+        "whitelist",
+        vec![Field::from("dave"), Field::from("127.0.0.9"), Field::Null],
+    )
+    .unwrap();
+    assert!(matches!(
+        doc.insert(
+            "whitelist",
+            vec![Field::from("erin"), Field::Null, Field::Null]
+        ),
+        Err(Error::RecordMismatch { .. })
+    ));
+    assert_eq!(
+        doc.update(
+            filter!("whitelist"; "joe", *, *),
+            &[None, None, Some(Field::Null)]
+        ),
+        Ok(1)
+    );
+    assert_eq!(
+        names(
+            doc.find(crate::Filter::with_predicates(
+                "whitelist".to_string(),
+                vec![Predicate::Any, Predicate::Any, Predicate::IsNull]
+            ))
+            .unwrap()
+        ),
+        vec!["joe", "bob", "carol", "dave"]
+    );
+}
+
+#[test]
+fn _4() {
+    let mut doc = load("note: name:ustr text:istr?\nnote joe \"null\"\nnote bob null\n").unwrap();
+    let found = doc.find(filter!("note"; *, *)).unwrap();
+    assert_eq!(found[0].args()[1], Field::from("null"));
+    assert_eq!(found[1].args()[1], Field::Null);
+    assert_eq!(found[0].to_rules_string(), "note joe \"null\"");
+
+    doc.update(
+        filter!("note"; "bob", *),
+        &[None, Some(Field::from("null"))],
+    )
+    .unwrap();
+    doc.save().unwrap();
+    assert_eq!(
+        doc.sources()[0].content(),
+        "note: name:ustr text:istr?\nnote joe \"null\"\nnote bob \"null\"\n"
+    );
+    assert_eq!(doc.find(filter!("note"; *, "null")).unwrap().len(), 2);
+    assert!(doc
+        .find(filter!("note"; *, Field::Null))
+        .unwrap()
+        .is_empty());
+
+    assert_eq!(Field::Null.try_sig(), None);
+    assert_eq!(Field::List(Vec::new()).try_sig(), None);
+    assert_eq!(Field::from("null").try_sig(), Some(Signature::IStr));
+}
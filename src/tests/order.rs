@@ -0,0 +1,87 @@
+use crate::{filter, Document, Field, Filter};
+
+use super::load;
+
+const CONTENT: &str = r#"
+rule:   ustr    istr indexed    u16
+rule    zed     10.0.0.1        3
+rule    amy     10.0.0.2        1
+rule    bob     10.0.0.1        2
+rule    mia     10.0.0.1        1
+rule    cat     10.0.0.2        3
+"#;
+
+fn names(found: Vec<&crate::Record>) -> Vec<&str> {
+    found
+        .into_iter()
+        .map(|r| r.args()[0].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn _1() {
+    let doc = load(CONTENT).unwrap();
+    for _ in 0..3 {
+        assert_eq!(
+            names(doc.find(filter!("rule"; *, *, *)).unwrap()),
+            vec!["zed", "amy", "bob", "mia", "cat"]
+        );
+        assert_eq!(
+            names(doc.find(filter!("rule"; *, *, < 3)).unwrap()),
+            vec!["amy", "bob", "mia"]
+        );
+        assert_eq!(
+            names(doc.find(filter!("rule"; *, "10.0.0.1", *)).unwrap()),
+            vec!["zed", "bob", "mia"]
+        );
+        assert_eq!(
+            names(doc.query("rule 2=1 or 0=zed").unwrap()),
+            vec!["zed", "amy", "mia"]
+        );
+    }
+
+    let first = |filter: Filter| doc.find_one(filter).unwrap().unwrap().line();
+    assert_eq!(first(filter!("rule"; *, *, *)), 3);
+    assert_eq!(first(filter!("rule"; *, *, 1)), 4);
+    assert_eq!(first(filter!("rule"; *, "10.0.0.2", *)), 4);
+    assert_eq!(first(filter!("rule"; "mia", "10.0.0.1", 1)), 6);
+}
+
+#[test]
+fn _2() {
+    let dir = std::env::temp_dir().join(format!("simplerecords-order-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("master.rules"),
+        "rule: ustr\nrule zed\ninclude second\nrule amy\ninclude third\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("second.rules"), "rule yan\nrule bob\n").unwrap();
+    std::fs::write(dir.join("third.rules"), "rule xia\n").unwrap();
+
+    let mut doc = Document::load(dir.join("master")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    doc.insert("rule", vec![Field::from("abe")]).unwrap();
+    doc.update(filter!("rule"; "yan"), &[Some(Field::from("ida"))])
+        .unwrap();
+    let found = doc.find(filter!("rule"; *)).unwrap();
+    assert_eq!(
+        names(found.clone()),
+        vec!["zed", "ida", "bob", "amy", "xia", "abe"]
+    );
+    assert_eq!(
+        found
+            .iter()
+            .map(|r| (r.location(), r.line()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("master", 2),
+            ("second", 1),
+            ("second", 2),
+            ("master", 4),
+            ("third", 1),
+            ("master", 0)
+        ]
+    );
+}
@@ -0,0 +1,136 @@
+use crate::{filter, Error, Filter, Record};
+
+use super::doc;
+
+const CONTENT: &str = r#"
+whitelist:  ustr    istr        u64
+whitelist   joe     127.0.0.1   300
+whitelist   bob     127.0.0.1   100
+whitelist   Alice   127.0.0.3   300
+whitelist   carol   127.0.0.4   200
+whitelist   Bob     127.0.0.5   400
+whitelist   dave    127.0.0.6   100
+"#;
+
+fn all() -> Filter {
+    filter!("whitelist"; *, *, *)
+}
+
+fn names(found: Vec<&Record>) -> Vec<String> {
+    found.into_iter().map(|r| r.args()[0].to_string()).collect()
+}
+
+#[test]
+fn _1() {
+    let doc = doc(CONTENT);
+    assert_eq!(
+        names(doc.find(all().with_sort(2, false)).unwrap()),
+        vec![
+            "\"bob\"",
+            "\"dave\"",
+            "\"carol\"",
+            "\"joe\"",
+            "\"Alice\"",
+            "\"Bob\""
+        ]
+    );
+    assert_eq!(
+        names(
+            doc.find(all().with_sort(2, true).with_sort(0, false))
+                .unwrap()
+        ),
+        vec![
+            "\"Bob\"",
+            "\"Alice\"",
+            "\"joe\"",
+            "\"carol\"",
+            "\"bob\"",
+            "\"dave\""
+        ]
+    );
+    assert_eq!(
+        names(
+            doc.find(all().with_sort(0, false).with_sort(2, true))
+                .unwrap()
+        ),
+        vec![
+            "\"Alice\"",
+            "\"Bob\"",
+            "\"bob\"",
+            "\"carol\"",
+            "\"dave\"",
+            "\"joe\""
+        ]
+    );
+}
+
+#[test]
+fn _2() {
+    let doc = doc(CONTENT);
+    let page = |offset, limit| {
+        names(
+            doc.find(
+                all()
+                    .with_sort(2, false)
+                    .with_offset(offset)
+                    .with_limit(limit),
+            )
+            .unwrap(),
+        )
+    };
+    assert_eq!(page(0, 2), vec!["\"bob\"", "\"dave\""]);
+    assert_eq!(page(2, 2), vec!["\"carol\"", "\"joe\""]);
+    assert_eq!(page(4, 2), vec!["\"Alice\"", "\"Bob\""]);
+    assert!(page(6, 2).is_empty());
+    assert!(page(0, 0).is_empty());
+
+    assert_eq!(
+        names(doc.find(all().with_offset(4)).unwrap()),
+        vec!["\"Bob\"", "\"dave\""]
+    );
+    assert_eq!(
+        doc.find_one(all().with_sort(2, true))
+            .unwrap()
+            .unwrap()
+            .args()[0]
+            .to_string(),
+        "\"Bob\""
+    );
+    assert_eq!(
+        doc.find_one(all().with_sort(2, true).with_offset(1))
+            .unwrap()
+            .unwrap()
+            .args()[0]
+            .to_string(),
+        "\"joe\""
+    );
+    assert!(doc
+        .find_one(filter!("whitelist"; "joe", "127.0.0.1", 300).with_limit(0))
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn _3() {
+    let mut doc = doc(CONTENT);
+    let removed = doc.remove(all().with_sort(2, false).with_limit(3)).unwrap();
+    assert_eq!(removed.len(), 3);
+    assert_eq!(
+        names(doc.find(all()).unwrap()),
+        vec!["\"joe\"", "\"Alice\"", "\"Bob\""]
+    );
+
+    assert!(matches!(
+        doc.find(all().with_sort(3, false)),
+        Err(Error::FilterMismatch { .. })
+    ));
+    assert_eq!(
+        all()
+            .with_sort(2, true)
+            .with_sort(0, false)
+            .with_offset(5)
+            .with_limit(10)
+            .to_string(),
+        "whitelist * * * | sort 2 desc 0 | offset 5 | limit 10"
+    );
+}
@@ -0,0 +1,64 @@
+use crate::{Error, Field, Filter, ParseError, Predicate, Signature};
+
+use super::doc;
+
+const CONTENT: &str = r#"
+whitelist:  ustr    istr        u64
+whitelist   joe     127.0.0.1   123456
+whitelist   bob     127.0.0.1   123457
+whitelist   Alice   127.0.0.3   123459
+"#;
+
+#[test]
+fn _1() {
+    let doc = doc(CONTENT);
+    let filter = doc
+        .parse_filter("whitelist", &["*", "127.0.0.1", "*"])
+        .unwrap();
+    assert_eq!(
+        filter,
+        Filter::with_predicates(
+            "whitelist".to_string(),
+            vec![
+                Predicate::Any,
+                Predicate::Eq(Field::IStr("127.0.0.1".to_string())),
+                Predicate::Any
+            ]
+        )
+    );
+    assert_eq!(doc.find(filter).unwrap().len(), 2);
+
+    let filter = doc
+        .parse_filter("whitelist", &["ALICE", "*", "123459"])
+        .unwrap();
+    assert_eq!(doc.find(filter).unwrap().len(), 1);
+}
+
+#[test]
+fn _2() {
+    let doc = doc(CONTENT);
+    match doc.parse_filter("whitelist", &["*", "*", "soon"]) {
+        Err(Error::ParseError {
+            reason: ParseError::TypeError { target, value, .. },
+            ..
+        }) => {
+            assert_eq!(target, Signature::U64);
+            assert_eq!(value, "soon");
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+    assert!(matches!(
+        doc.parse_filter("whitelist", &["*", "*"]),
+        Err(Error::ParseError {
+            reason: ParseError::ArgumentLengthMismatch {
+                expected: 3,
+                got: 2
+            },
+            ..
+        })
+    ));
+    assert!(matches!(
+        doc.parse_filter("blacklist", &["*"]),
+        Err(Error::NoDefinition { .. })
+    ));
+}
@@ -0,0 +1,203 @@
+use std::ops::Bound;
+
+use crate::{filter, Aggregate, Document, Error, Field, ParseError, Pattern, Signature};
+
+use super::load;
+
+const CONTENT: &str = r#"
+acl:    host:istr pattern   port:u16 pattern    action:istr
+acl     *                   *                   deny
+acl     *.example.com       443                 allow
+acl     www.example.com     *                   log
+acl     *.com               1000..2000          deny
+acl     api.example.com     443                 allow
+acl     ?.example.com       ..=1024             allow
+"#;
+
+fn rules(doc: &Document, host: &str, port: u16) -> Vec<String> {
+    doc.match_rules("acl", &[Field::from(host), Field::U16(port)])
+        .unwrap()
+        .into_iter()
+        .map(|r| {
+            format!(
+                "{} {}",
+                r.args()[0].to_rules_string(),
+                r.args()[1].to_rules_string()
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn _1() {
+    let doc = load(CONTENT).unwrap();
+    assert_eq!(
+        rules(&doc, "api.example.com", 443),
+        vec!["api.example.com 443", "*.example.com 443", "* *",]
+    );
+    assert_eq!(
+        rules(&doc, "www.example.com", 1500),
+        vec!["www.example.com *", "*.com 1000..2000", "* *"]
+    );
+    assert_eq!(
+        rules(&doc, "a.example.com", 80),
+        vec!["?.example.com ..=1024", "* *"]
+    );
+    assert_eq!(rules(&doc, "example.org", 22), vec!["* *"]);
+}
+
+#[test]
+fn _2() {
+    let doc = load(CONTENT).unwrap();
+    let schema = doc.schema("acl").unwrap();
+    assert!(schema.columns()[0].pattern());
+    assert!(!schema.columns()[2].pattern());
+    assert_eq!(
+        schema.to_rules_string(),
+        "acl: host:istr pattern port:u16 pattern action:istr"
+    );
+
+    let found = doc.find(filter!("acl"; *, *, "log")).unwrap();
+    assert_eq!(found[0].args()[1].as_pattern(), Some(&Pattern::Any));
+    let found = doc.find(filter!("acl"; "api.example.com", *, *)).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(
+        doc.find(filter!("acl"; *, Pattern::Any, *)).unwrap().len(),
+        2
+    );
+    assert!(doc
+        .find(filter!("acl"; *, >= 500u16, *))
+        .unwrap()
+        .is_empty());
+    assert_eq!(doc.find(filter!("acl"; *, <= 443u16, *)).unwrap().len(), 2);
+    for aggregate in [Aggregate::Sum(1), Aggregate::Average(1), Aggregate::Max(1)] {
+        assert_eq!(
+            doc.aggregate(filter!("acl"; *, *, *), &[aggregate]),
+            Err(Error::AggregateMismatch {
+                label: "acl".to_string(),
+                aggregate,
+                got: Signature::U16
+            })
+        );
+    }
+    assert_eq!(
+        doc.aggregate(
+            filter!("acl"; *, *, *),
+            &[Aggregate::Count, Aggregate::Min(2)]
+        ),
+        Ok(vec![Some(Field::U64(6)), Some(Field::from("allow"))])
+    );
+
+    let copy = load(&doc.to_rules_string()).unwrap();
+    assert_eq!(
+        rules(&copy, "www.example.com", 1500),
+        rules(&doc, "www.example.com", 1500)
+    );
+    assert_eq!(
+        doc.match_rules(
+            "acl",
+            &[
+                Field::from("www.example.com"),
+                Field::U16(443),
+                Field::from("allow")
+            ]
+        )
+        .unwrap()
+        .len(),
+        1
+    );
+}
+
+#[test]
+fn _3() {
+    let mut doc = load(CONTENT).unwrap();
+    doc.insert(
+        "acl",
+        vec![
+            Field::from(Pattern::Glob("*.org".to_string())),
+            Field::from(Pattern::Range(
+                Bound::Included(Field::U16(20)),
+                Bound::Excluded(Field::U16(23)),
+            )),
+            Field::from("allow"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(rules(&doc, "example.org", 22), vec!["*.org 20..23", "* *"]);
+    assert!(matches!(
+        doc.insert(
+            "acl",
+            vec![
+                Field::from("x"),
+                Field::from("y"),
+                Field::from(Pattern::Any)
+            ]
+        ),
+        Err(Error::RecordMismatch { .. })
+    ));
+
+    doc.remove(filter!("acl"; Pattern::Any, Pattern::Any, *))
+        .unwrap();
+    assert_eq!(rules(&doc, "example.net", 22), Vec::<String>::new());
+    assert!(matches!(
+        doc.match_rules("acl", &[Field::from(Pattern::Any)]),
+        Err(Error::RecordMismatch { .. })
+    ));
+    assert!(matches!(
+        doc.match_rules("acl", &[Field::from("a"), Field::from("b")]),
+        Err(Error::RecordMismatch { .. })
+    ));
+}
+
+#[test]
+fn _4() {
+    let doc = load(
+        r#"
+uid:    name:ustr pattern   uid:u32 pattern     net:cidr
+uid     ROOT                0                   10.0.0.0/8
+uid     *                   1000..              10.0.0.0/8
+uid     *                   1000..              10.1.0.0/16
+"#,
+    )
+    .unwrap();
+    let found = doc
+        .match_rules(
+            "uid",
+            &[
+                Field::from("joe"),
+                Field::U32(1001),
+                Field::from("10.1.2.3"),
+            ],
+        )
+        .unwrap();
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].args()[2].to_string(), "10.1.0.0/16");
+    assert_eq!(
+        doc.match_rules("uid", &[Field::from("root"), Field::U32(0)])
+            .unwrap()
+            .len(),
+        1
+    );
+
+    assert!(matches!(
+        load("t: istr\nt *"),
+        Ok(doc) if doc.find(filter!("t"; "*")).unwrap().len() == 1
+    ));
+    for content in ["t: cidr pattern", "t: [u16] pattern"] {
+        assert!(matches!(
+            load(content),
+            Err(Error::ParseError {
+                reason: ParseError::IllegalConstraint { .. },
+                ..
+            })
+        ));
+    }
+    assert!(matches!(
+        load("t: u8 pattern\nt 1..300"),
+        Err(Error::ParseError {
+            line: 2,
+            reason: ParseError::TypeError { .. },
+            ..
+        })
+    ));
+}
@@ -0,0 +1,141 @@
+use crate::{Document, Error, Field, Filter, Predicate};
+
+use super::doc;
+
+const CONTENT: &str = r#"
+whitelist:  ustr    istr        u64     char    f32
+whitelist   joe     127.0.0.1   123456  a       0.5
+whitelist   bob     127.0.0.1   123457  b       -1
+whitelist   Alice   127.0.0.3   123459  c       2.5
+whitelist   carol   127.0.0.4   0       d       -0.25
+"#;
+
+fn names(doc: &Document, args: Vec<Predicate>) -> Vec<String> {
+    let mut found = doc
+        .find(Filter::with_predicates("whitelist".to_string(), args))
+        .unwrap()
+        .into_iter()
+        .map(|r| r.args()[0].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    found.sort();
+    found
+}
+
+fn column(i: usize, pred: Predicate) -> Vec<Predicate> {
+    let mut args = vec![Predicate::Any; 5];
+    args[i] = pred;
+    args
+}
+
+#[test]
+fn _1() {
+    let doc = doc(CONTENT);
+    assert_eq!(
+        names(&doc, column(2, Predicate::Gt(Field::U64(123456)))),
+        vec!["Alice", "bob"]
+    );
+    assert_eq!(
+        names(&doc, column(2, Predicate::Ge(Field::U64(123456)))),
+        vec!["Alice", "bob", "joe"]
+    );
+    assert_eq!(
+        names(&doc, column(2, Predicate::Lt(Field::U32(123457)))),
+        vec!["carol", "joe"]
+    );
+    assert_eq!(
+        names(&doc, column(2, Predicate::Le(Field::U8(0)))),
+        vec!["carol"]
+    );
+}
+
+#[test]
+fn _2() {
+    let doc = doc(CONTENT);
+    assert_eq!(
+        names(
+            &doc,
+            column(2, Predicate::Between(Field::U64(1), Field::U64(123457)))
+        ),
+        vec!["bob", "joe"]
+    );
+    assert_eq!(
+        names(
+            &doc,
+            column(2, Predicate::Between(Field::U64(123457), Field::U64(1)))
+        ),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        names(
+            &doc,
+            column(1, Predicate::Ne(Field::IStr("127.0.0.1".to_string())))
+        ),
+        vec!["Alice", "carol"]
+    );
+}
+
+#[test]
+fn _3() {
+    let doc = doc(CONTENT);
+    assert_eq!(
+        names(
+            &doc,
+            column(0, Predicate::Lt(Field::IStr("BOB".to_string())))
+        ),
+        vec!["Alice"]
+    );
+    assert_eq!(
+        names(&doc, column(0, Predicate::Ge(Field::IStr("C".to_string())))),
+        vec!["carol", "joe"]
+    );
+    assert_eq!(
+        names(
+            &doc,
+            column(3, Predicate::Between(Field::Char('b'), Field::Char('c')))
+        ),
+        vec!["Alice", "bob"]
+    );
+}
+
+#[test]
+fn _4() {
+    let doc = doc(CONTENT);
+    assert_eq!(
+        names(&doc, column(4, Predicate::Lt(Field::F64(0.0)))),
+        vec!["bob", "carol"]
+    );
+    assert_eq!(
+        names(&doc, column(4, Predicate::Ge(Field::F32(0.5)))),
+        vec!["Alice", "joe"]
+    );
+}
+
+#[test]
+fn _5() {
+    let doc = doc(CONTENT);
+    let mut args = column(0, Predicate::Ne(Field::IStr("joe".to_string())));
+    args[1] = Predicate::Eq(Field::IStr("127.0.0.1".to_string()));
+    args[2] = Predicate::Ge(Field::U64(100));
+    assert_eq!(names(&doc, args), vec!["bob"]);
+}
+
+#[test]
+fn _6() {
+    let doc = doc(CONTENT);
+    let filter = Filter::with_predicates(
+        "whitelist".to_string(),
+        column(2, Predicate::Gt(Field::Bool(true))),
+    );
+    assert!(matches!(
+        doc.find(filter),
+        Err(Error::FilterMismatch { .. })
+    ));
+    assert_eq!(
+        Filter::with_predicates(
+            "whitelist".to_string(),
+            column(2, Predicate::Between(Field::U8(1), Field::U8(2)))
+        )
+        .to_string(),
+        "whitelist * * 1..=2 * *"
+    );
+}
@@ -0,0 +1,152 @@
+use crate::{Condition, Document, Error, Field, ParseError, Predicate, Query, QueryError};
+
+use super::doc;
+
+const CONTENT: &str = r#"
+whitelist:  ustr    istr        u64
+whitelist   joe     127.0.0.1   123456
+whitelist   bob     127.0.0.1   100
+whitelist   Alice   127.0.0.3   123459
+whitelist   carol   "my host"   0
+whitelist   bob     127.0.0.5   123457
+"#;
+
+fn names(doc: &Document, query: &str) -> Vec<String> {
+    doc.query(query)
+        .unwrap()
+        .into_iter()
+        .map(|r| r.args()[0].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn sorted(doc: &Document, query: &str) -> Vec<String> {
+    let mut found = names(doc, query);
+    found.sort();
+    found
+}
+
+fn column(doc: &Document, query: &str) -> Option<(usize, QueryError)> {
+    match Query::parse(doc, query) {
+        Err(Error::QueryError { column, reason }) => Some((column, reason)),
+        _ => None,
+    }
+}
+
+#[test]
+fn _1() {
+    let doc = doc(CONTENT);
+    assert_eq!(sorted(&doc, "whitelist * 127.0.0.1 *"), vec!["bob", "joe"]);
+    assert_eq!(sorted(&doc, "whitelist * 'my host' *"), vec!["carol"]);
+    assert_eq!(sorted(&doc, r#"whitelist 0="joe""#), vec!["joe"]);
+    assert_eq!(sorted(&doc, "whitelist 0 = BOB  2 > 1000"), vec!["bob"]);
+    assert_eq!(
+        sorted(&doc, "whitelist (0=joe or 0=bob) and not 2<1000"),
+        vec!["bob", "joe"]
+    );
+    assert_eq!(
+        sorted(&doc, "whitelist 1!=127.0.0.1 not(0=alice)"),
+        vec!["bob", "carol"]
+    );
+    assert_eq!(sorted(&doc, "whitelist").len(), 5);
+    assert_eq!(sorted(&doc, "whitelist 0=*").len(), 5);
+}
+
+#[test]
+fn _2() {
+    let doc = doc(CONTENT);
+    assert_eq!(
+        names(&doc, "whitelist | sort 2 desc"),
+        vec!["Alice", "bob", "joe", "bob", "carol"]
+    );
+    assert_eq!(
+        names(
+            &doc,
+            "whitelist 2>=100 | sort 0 asc 2 desc | offset 1 | limit 2"
+        ),
+        vec!["bob", "bob"]
+    );
+    assert_eq!(
+        names(&doc, "whitelist | sort 0 2 | limit 3"),
+        vec!["Alice", "bob", "bob"]
+    );
+}
+
+#[test]
+fn _3() {
+    let doc = doc(CONTENT);
+    let query = Query::parse(&doc, r#"whitelist 0="or" | sort 1 | limit 1"#).unwrap();
+    assert_eq!(
+        query.filter().condition(),
+        &Condition::Field(
+            0,
+            Predicate::Eq(Field::UStr {
+                original: "or".to_string(),
+                lower: "or".to_string()
+            })
+        )
+    );
+    assert_eq!(query.sort(), &[(1, false)]);
+    assert_eq!(query.limit(), Some(1));
+    assert_eq!(query.offset(), 0);
+}
+
+#[test]
+fn _4() {
+    let doc = doc(CONTENT);
+    assert!(matches!(
+        column(&doc, "whitelist 2>soon"),
+        Some((13, QueryError::ParseError(ParseError::TypeError { .. })))
+    ));
+    assert_eq!(
+        column(&doc, "whitelist 3=1"),
+        Some((
+            11,
+            QueryError::UnknownColumn {
+                got: "3".to_string()
+            }
+        ))
+    );
+    assert_eq!(
+        column(&doc, "whitelist (0=joe"),
+        Some((
+            17,
+            QueryError::Expected {
+                expected: "`)`".to_string(),
+                got: None
+            }
+        ))
+    );
+    assert_eq!(
+        column(&doc, "whitelist 0=joe | reverse"),
+        Some((
+            19,
+            QueryError::UnknownStage {
+                got: "reverse".to_string()
+            }
+        ))
+    );
+    assert_eq!(
+        column(&doc, "whitelist * *"),
+        Some((
+            11,
+            QueryError::ParseError(ParseError::ArgumentLengthMismatch {
+                expected: 3,
+                got: 2
+            })
+        ))
+    );
+    assert_eq!(
+        column(&doc, "whitelist 0>* | limit ten"),
+        Some((
+            13,
+            QueryError::Expected {
+                expected: "value".to_string(),
+                got: Some("*".to_string())
+            }
+        ))
+    );
+    assert!(matches!(
+        Query::parse(&doc, "blacklist *"),
+        Err(Error::NoDefinition { .. })
+    ));
+}
@@ -0,0 +1,212 @@
+use crate::{Document, Field, Filter, SyntaxTree};
+
+use super::{doc, load};
+
+const CONTENT: &str = r#"# users of the service
+#               username    IP          expiry
+whitelist:      ustr        istr        u64
+whitelist       joe         127.0.0.1   123456  # first user
+whitelist       bob         127.0.0.1   123457
+
+scope friends
+whitelist       sirius      127.0.0.5   123451
+/* alice has
+   a long comment */ whitelist alice 127.0.0.3 123459
+"#;
+
+fn name(s: &str) -> Filter {
+    Filter::new(
+        "whitelist".to_string(),
+        &[Some(Field::IStr(s.to_string())), None, None],
+    )
+}
+
+fn rewritten(doc: &Document) -> String {
+    let mut changed = doc.rewrite().unwrap();
+    assert_eq!(changed.len(), 1);
+    changed.remove(0).1
+}
+
+#[test]
+fn _1() {
+    for s in [
+        CONTENT,
+        "",
+        "no newline",
+        "a 'b # c' \"d /* e\" # f\n\n\t\tg/* h\n\n*/i\r\n",
+    ] {
+        assert_eq!(SyntaxTree::parse(s).unwrap().to_string(), s);
+    }
+}
+
+#[test]
+fn _2() {
+    let tree = SyntaxTree::parse(CONTENT).unwrap();
+    assert_eq!(
+        tree.words(4),
+        vec!["whitelist", "joe", "127.0.0.1", "123456"]
+    );
+    assert_eq!(
+        tree.words(10),
+        vec!["whitelist", "alice", "127.0.0.3", "123459"]
+    );
+    assert_eq!(tree.words(9), Vec::<&str>::new());
+}
+
+#[test]
+fn _3() {
+    let doc = doc(CONTENT);
+    assert!(doc.rewrite().unwrap().is_empty());
+}
+
+#[test]
+fn _4() {
+    let mut doc = doc(CONTENT);
+    doc.update(
+        name("joe"),
+        &[
+            None,
+            Some(Field::IStr("10.0.0.1".to_string())),
+            Some(Field::U64(1)),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(
+        rewritten(&doc),
+        CONTENT.replace(
+            "whitelist       joe         127.0.0.1   123456  # first user",
+            "whitelist       joe         10.0.0.1    1       # first user"
+        )
+    );
+}
+
+#[test]
+fn _5() {
+    let mut doc = doc(CONTENT);
+    doc.remove(name("bob")).unwrap();
+    doc.remove(name("alice")).unwrap();
+
+    assert_eq!(
+        rewritten(&doc),
+        CONTENT
+            .replace("whitelist       bob         127.0.0.1   123457\n", "")
+            .replace(" whitelist alice 127.0.0.3 123459", "")
+    );
+}
+
+#[test]
+fn _6() {
+    let mut doc = doc(CONTENT);
+    doc.insert(
+        "whitelist",
+        vec![
+            Field::IStr("zoe".to_string()),
+            Field::IStr("my host".to_string()),
+            Field::U64(1),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(
+        rewritten(&doc),
+        CONTENT.replace(
+            "whitelist       bob         127.0.0.1   123457\n",
+            "whitelist       bob         127.0.0.1   123457\nwhitelist       zoe         \"my host\"   1\n"
+        )
+    );
+}
+
+#[test]
+fn _7() {
+    let expected = "scope types\nuser: ustr u8 # name age\nuser  joe  17\nuser  bob  18\nscope\n";
+    let mut doc = load("scope types\nuser: ustr u8 # name age\nscope\n").unwrap();
+    doc.insert("user", vec![Field::IStr("joe".to_string()), Field::U8(17)])
+        .unwrap();
+    doc.insert("user", vec![Field::IStr("bob".to_string()), Field::U8(18)])
+        .unwrap();
+    assert_eq!(rewritten(&doc), expected);
+
+    doc.save().unwrap();
+    assert_eq!(doc.sources()[0].content(), expected);
+    assert!(doc.rewrite().unwrap().is_empty());
+
+    let bob = Filter::new("user".to_string(), &[None, Some(Field::U8(18))]);
+    assert_eq!(doc.find_one(bob).unwrap().unwrap().line(), 4);
+}
+
+#[test]
+fn _8() {
+    let dir = std::env::temp_dir().join(format!("simplerecords-rewrite-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("master.rules"), "include whitelist # all users\n").unwrap();
+    std::fs::write(dir.join("whitelist.rules"), CONTENT).unwrap();
+
+    let mut doc = Document::load(dir.join("master")).unwrap();
+    doc.update(name("sirius"), &[None, None, Some(Field::U64(0))])
+        .unwrap();
+    doc.save().unwrap();
+
+    let saved = std::fs::read_to_string(dir.join("whitelist.rules")).unwrap();
+    let master = std::fs::read_to_string(dir.join("master.rules")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(
+        saved,
+        CONTENT.replace(
+            "whitelist       sirius      127.0.0.5   123451",
+            "whitelist       sirius      127.0.0.5   0"
+        )
+    );
+    assert_eq!(master, "include whitelist # all users\n");
+    assert_eq!(
+        doc.find_one(name("sirius")).unwrap().unwrap().args()[2],
+        Field::U64(0)
+    );
+}
+
+#[test]
+fn _9() {
+    let mut doc =
+        load("w: ustr istr u64=0 u8=1 # name ip expiry level\nw joe 1.1.1.1 # joe\n").unwrap();
+    let joe = Filter::new(
+        "w".to_string(),
+        &[Some(Field::IStr("joe".to_string())), None, None, None],
+    );
+
+    doc.update(joe.clone(), &[None, None, None, Some(Field::U8(1))])
+        .unwrap();
+    assert!(doc.rewrite().unwrap().is_empty());
+
+    doc.update(joe.clone(), &[None, None, Some(Field::U64(5)), None])
+        .unwrap();
+    let expected = "w: ustr istr u64=0 u8=1 # name ip expiry level\nw joe 1.1.1.1 5 # joe\n";
+    assert_eq!(rewritten(&doc), expected);
+
+    doc.save().unwrap();
+    assert_eq!(doc.sources()[0].content(), expected);
+    assert_eq!(doc.find_one(joe).unwrap().unwrap().args()[2], Field::U64(5));
+}
+
+#[test]
+fn _10() {
+    let mut doc = doc(CONTENT);
+    for name in ["zoe", "adam", "mia"] {
+        doc.insert(
+            "whitelist",
+            vec![Field::from(name), Field::from("127.0.0.9"), Field::U64(1)],
+        )
+        .unwrap();
+    }
+
+    assert_eq!(
+        rewritten(&doc),
+        CONTENT.replace(
+            "whitelist       bob         127.0.0.1   123457\n",
+            "whitelist       bob         127.0.0.1   123457\n\
+             whitelist       zoe         127.0.0.9   1\n\
+             whitelist       adam        127.0.0.9   1\n\
+             whitelist       mia         127.0.0.9   1\n"
+        )
+    );
+}
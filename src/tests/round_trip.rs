@@ -0,0 +1,173 @@
+use crate::{Document, Options};
+
+use super::load;
+
+fn dump(doc: &Document) -> Vec<String> {
+    let mut sets = doc.record_sets().collect::<Vec<_>>();
+    sets.sort_by_key(|rs| rs.schema().label());
+
+    let mut out = Vec::new();
+    for rs in sets {
+        out.push(format!(
+            "{:?} {}",
+            crate::Pass::split_location(rs.schema().location()).1,
+            rs.schema().to_rules_string()
+        ));
+
+        for record in rs.records() {
+            out.push(format!(
+                "{:?} {}",
+                crate::Pass::split_location(record.location()).1,
+                record.to_rules_string()
+            ));
+        }
+    }
+
+    out
+}
+
+fn round_trip(content: &str) {
+    let doc = load(content).unwrap();
+    let written = doc.to_rules_string();
+    let copy = load(&written).unwrap();
+    assert_eq!(dump(&doc), dump(&copy), "{written}");
+    assert_eq!(written, copy.to_rules_string());
+}
+
+#[test]
+fn _1() {
+    round_trip(
+        r##"
+strings: istr ustr
+strings plain Plain
+strings "with space" 'single \' quote'
+strings "double \" quote" "back\\slash"
+strings "colon: here" "# not a comment /* either */"
+strings "" "  padded  "
+"##,
+    );
+}
+
+#[test]
+fn _2() {
+    round_trip(
+        r#"
+chars: char
+chars a
+chars "'"
+chars '"'
+chars ' '
+chars \\
+chars '#'
+chars ':'
+"#,
+    );
+}
+
+#[test]
+fn _3() {
+    round_trip(
+        r#"
+ints: i8 i16 i32 i64 u8 u16 u32 u64
+ints -128 -32768 -2147483648 -9223372036854775808 0 0 0 0
+ints 127 32767 2147483647 9223372036854775807 255 65535 4294967295 18446744073709551615
+floats: f32 f64 bool
+floats 0.1 0.1 true
+floats -1e30 1e300 false
+floats inf -inf true
+floats 3.4028235e38 5e-324 false
+"#,
+    );
+}
+
+#[test]
+fn _4() {
+    round_trip(
+        r#"
+unit:
+unit
+scope first
+pair: ustr u8
+pair a 1
+scope "second scope"
+pair b 2
+scope
+pair c 3
+"#,
+    );
+}
+
+#[test]
+fn _5() {
+    let doc = Options::default()
+        .with("user: ustr u8")
+        .with("user joe 17")
+        .open()
+        .unwrap();
+
+    assert_eq!(doc.to_rules_string(), "user: ustr u8\nuser  joe  17\n");
+}
+
+#[test]
+fn _6() {
+    let dir = std::env::temp_dir().join(format!("simplerecords-round-trip-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let doc = Options::default()
+        .with("user: ustr u8")
+        .with("user joe 17")
+        .with("scope other")
+        .with("user bob 18")
+        .open()
+        .unwrap();
+
+    let files = doc.to_rules_files("_init");
+    assert_eq!(files[0].0, "_init.rules");
+    for (name, content) in files.iter() {
+        std::fs::write(dir.join(name), content).unwrap();
+    }
+
+    let copy = Document::load(dir.join("_init")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(dump(&doc), dump(&copy));
+}
+
+#[test]
+fn _7() {
+    let dir = std::env::temp_dir().join(format!(
+        "simplerecords-include-order-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(dir.join("copy")).unwrap();
+    std::fs::write(
+        dir.join("master.rules"),
+        "user: ustr u8\nuser bob 20\ninclude zoe\nuser eve 50\ninclude adam\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("zoe.rules"), "user zoe 30\n").unwrap();
+    std::fs::write(dir.join("adam.rules"), "user adam 40\n").unwrap();
+
+    let doc = Document::load(dir.join("master")).unwrap();
+    let written = doc.to_rules_string();
+    assert_eq!(
+        written,
+        "user: ustr u8\nuser  bob  20\nuser  zoe  30\nuser  eve  50\nuser  adam 40\n"
+    );
+    let copy = load(&written).unwrap();
+    assert_eq!(dump(&copy), dump(&doc));
+
+    let files = doc.to_rules_files("master");
+    assert_eq!(
+        files[0],
+        (
+            "master.rules".to_string(),
+            "user: ustr u8\nuser  bob  20\ninclude zoe\nuser  eve  50\ninclude adam\n".to_string()
+        )
+    );
+    for (name, content) in files.iter() {
+        std::fs::write(dir.join("copy").join(name), content).unwrap();
+    }
+    let copy = Document::load(dir.join("copy").join("master")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(dump(&copy), dump(&doc));
+}
@@ -79,3 +79,28 @@ fn _8() {
         ])
     )
 }
+
+#[test]
+fn _9() {
+    assert_eq!(
+        Pass::split_args("abc '' ' def ' ghi"),
+        Ok(vec![
+            "abc".to_string(),
+            "".to_string(),
+            " def ".to_string(),
+            "ghi".to_string(),
+        ])
+    )
+}
+
+#[test]
+fn a() {
+    assert_eq!(
+        Pass::split_args("'a\\\\' b\\\\c \\'d"),
+        Ok(vec![
+            "a\\".to_string(),
+            "b\\c".to_string(),
+            "'d".to_string(),
+        ])
+    )
+}
@@ -0,0 +1,138 @@
+use crate::{filter, Error, Field, ParseError, Signature, Validity};
+
+use super::load;
+
+const CONTENT: &str = r#"
+cert:       name:istr   from:date not_before    to:date? not_after
+cert        web         2024-01-01              2024-12-31
+cert        mail        2024-06-01              2025-05-31
+cert        root        2020-01-01              null
+
+whitelist:  name:ustr   expiry:u64 expires
+whitelist   joe         1704067200
+whitelist   bob         1735689600
+
+session:    name:istr   until:datetime expires
+session     a           "2024-06-01T12:00:00Z"
+"#;
+
+fn names(records: Vec<&crate::Record>) -> Vec<String> {
+    records
+        .into_iter()
+        .map(|r| r.args()[0].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn date(s: &str) -> Field {
+    Signature::Date.parse(s, "", 0).unwrap()
+}
+
+fn datetime(s: &str) -> Field {
+    Signature::DateTime.parse(s, "", 0).unwrap()
+}
+
+#[test]
+fn _1() {
+    let doc = load(CONTENT).unwrap();
+    let schema = doc.schema("cert").unwrap();
+    assert_eq!(schema.columns()[1].validity(), Some(Validity::NotBefore));
+    assert_eq!(schema.columns()[2].validity(), Some(Validity::NotAfter));
+    assert_eq!(schema.columns()[0].validity(), None);
+    assert_eq!(
+        schema.to_rules_string(),
+        "cert: name:istr from:date not_before to:date? not_after"
+    );
+
+    let certs = |instant: &Field| names(doc.find_at(filter!("cert"; *, *, *), instant).unwrap());
+    assert_eq!(certs(&date("2024-03-01")), vec!["web", "root"]);
+    assert_eq!(certs(&date("2024-12-31")), vec!["web", "mail", "root"]);
+    assert_eq!(certs(&date("2025-01-01")), vec!["mail", "root"]);
+    assert_eq!(certs(&date("2019-12-31")), Vec::<String>::new());
+    assert_eq!(
+        certs(&datetime("2024-12-31T23:59:59+00:00")),
+        vec!["web", "mail", "root"]
+    );
+    assert_eq!(
+        names(
+            doc.find_at(filter!("cert"; "web", *, *), &date("2024-03-01"))
+                .unwrap()
+        ),
+        vec!["web"]
+    );
+}
+
+#[test]
+fn _2() {
+    let doc = load(CONTENT).unwrap();
+    let whitelist =
+        |instant: &Field| names(doc.find_at(filter!("whitelist"; *, *), instant).unwrap());
+    assert_eq!(whitelist(&Field::U64(1704067199)), vec!["joe", "bob"]);
+    assert_eq!(whitelist(&Field::U64(1704067200)), vec!["bob"]);
+    assert_eq!(whitelist(&date("2024-06-01")), vec!["bob"]);
+    assert_eq!(
+        whitelist(&datetime("2025-01-01T00:00:00Z")),
+        Vec::<String>::new()
+    );
+
+    assert_eq!(
+        names(
+            doc.find_at(
+                filter!("session"; *, *),
+                &datetime("2024-06-01T13:59:59+02:00")
+            )
+            .unwrap()
+        ),
+        vec!["a"]
+    );
+    assert!(matches!(
+        doc.find_at(filter!("cert"; *, *, *), &Field::from("soon")),
+        Err(Error::FilterMismatch { .. })
+    ));
+}
+
+#[test]
+fn _3() {
+    let doc = load(CONTENT).unwrap();
+    assert_eq!(
+        names(doc.expired(&date("2025-01-01")).unwrap()),
+        vec!["web", "a", "joe", "bob"]
+    );
+    assert_eq!(
+        names(doc.expired(&datetime("2024-06-01T00:00:00Z")).unwrap()),
+        vec!["joe"]
+    );
+    assert_eq!(
+        names(
+            doc.get("cert")
+                .unwrap()
+                .expired(&date("2030-01-01"))
+                .unwrap()
+        ),
+        vec!["web", "mail"]
+    );
+    assert!(doc.expired(&Field::now()).unwrap().len() >= 5);
+}
+
+#[test]
+fn _4() {
+    for content in [
+        "t: istr expires",
+        "t: [u64] not_after",
+        "t: bool not_before",
+    ] {
+        assert!(matches!(
+            load(content),
+            Err(Error::ParseError {
+                reason: ParseError::IllegalConstraint { .. },
+                ..
+            })
+        ));
+    }
+
+    let doc = load("t: istr\nt a").unwrap();
+    assert!(doc.expired(&Field::now()).unwrap().is_empty());
+    assert_eq!(
+        doc.find_at(filter!("t"; *), &Field::now()).unwrap().len(),
+        1
+    );
+}
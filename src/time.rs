@@ -0,0 +1,220 @@
+/// Duration units, largest first.
+const UNITS: &[(char, i64)] = &[
+    ('w', 7 * 86400),
+    ('d', 86400),
+    ('h', 3600),
+    ('m', 60),
+    ('s', 1),
+];
+
+/// Returns days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+pub(crate) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Returns year, month and day of days since 1970-01-01.
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Parse a number of exactly `len` digits.
+fn digits(s: &str, len: usize) -> Result<u32, String> {
+    if s.len() != len || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("expected {len} digits, got `{s}`"));
+    }
+    Ok(s.parse().unwrap())
+}
+
+/// Parse `YYYY-MM-DD` to days since 1970-01-01.
+pub(crate) fn parse_date(s: &str) -> Result<i64, String> {
+    let mut parts = s.splitn(3, '-');
+    let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err("expected YYYY-MM-DD".to_string());
+    };
+
+    let year = digits(year, 4)? as i64;
+    let month = digits(month, 2)?;
+    if !(1..=12).contains(&month) {
+        return Err(format!("month {month} out of range"));
+    }
+    let day = digits(day, 2)?;
+    if !(1..=days_in_month(year, month)).contains(&day) {
+        return Err(format!("day {day} out of range"));
+    }
+
+    Ok(days_from_civil(year, month, day))
+}
+
+/// Parse RFC 3339 `YYYY-MM-DDTHH:MM:SS[.frac](Z|+HH:MM|-HH:MM)` to seconds since epoch, nanoseconds and offset in minutes.
+/// - A date alone is midnight UTC.
+pub(crate) fn parse_datetime(s: &str) -> Result<(i64, u32, i16), String> {
+    let Some((date, time)) = s.split_once(['T', 't']) else {
+        return Ok((parse_date(s)? * 86400, 0, 0));
+    };
+    let date = parse_date(date)?;
+
+    let (time, offset) = if let Some(time) = time.strip_suffix(['Z', 'z']) {
+        (time, 0)
+    } else {
+        let at = time
+            .rfind(['+', '-'])
+            .ok_or("expected offset, `Z` or `+HH:MM`")?;
+        let (time, offset) = time.split_at(at);
+        let (hours, minutes) = offset[1..]
+            .split_once(':')
+            .ok_or("expected offset as `+HH:MM`")?;
+        let (hours, minutes) = (digits(hours, 2)?, digits(minutes, 2)?);
+        if hours > 23 || minutes > 59 {
+            return Err("offset out of range".to_string());
+        }
+        let minutes = (hours * 60 + minutes) as i16;
+        (
+            time,
+            if offset.starts_with('-') {
+                -minutes
+            } else {
+                minutes
+            },
+        )
+    };
+
+    let (time, nanos) = match time.split_once('.') {
+        Some((time, frac)) => {
+            if frac.is_empty() || frac.len() > 9 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(format!("invalid fraction `{frac}`"));
+            }
+            (time, format!("{frac:0<9}").parse::<u32>().unwrap())
+        }
+        None => (time, 0),
+    };
+
+    let mut parts = time.splitn(3, ':');
+    let (Some(hour), Some(minute), Some(second)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err("expected HH:MM:SS".to_string());
+    };
+    let (hour, minute, second) = (digits(hour, 2)?, digits(minute, 2)?, digits(second, 2)?);
+    if hour > 23 || minute > 59 || second > 59 {
+        return Err("time out of range".to_string());
+    }
+
+    let secs = date * 86400 + (hour * 3600 + minute * 60 + second) as i64 - offset as i64 * 60;
+    Ok((secs, nanos, offset))
+}
+
+/// Parse units of time, as in `30d` or `1h30m`, to seconds.
+/// - Units are `w`, `d`, `h`, `m` and `s`, each written at most once, largest first.
+pub(crate) fn parse_duration(s: &str) -> Result<i64, String> {
+    let (negative, mut rest) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    if rest.is_empty() {
+        return Err("expected a number and unit, as in `30d`".to_string());
+    }
+
+    let mut secs: i64 = 0;
+    let mut units = UNITS.iter();
+
+    while !rest.is_empty() {
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or(format!("missing unit after `{rest}`"))?;
+        let (n, after) = rest.split_at(end);
+        let n = n
+            .parse::<i64>()
+            .map_err(|_| format!("expected a number before `{after}`"))?;
+        let unit = after.chars().next().unwrap();
+        let (_, size) = units
+            .by_ref()
+            .find(|(u, _)| *u == unit)
+            .ok_or(format!("unexpected unit `{unit}`"))?;
+
+        secs = n
+            .checked_mul(*size)
+            .and_then(|n| secs.checked_add(n))
+            .ok_or("duration too long")?;
+        rest = &after[1..];
+    }
+
+    Ok(if negative { -secs } else { secs })
+}
+
+/// Write days since 1970-01-01 as `YYYY-MM-DD`.
+pub(crate) fn format_date(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Write seconds since epoch as RFC 3339 in the offset.
+pub(crate) fn format_datetime(secs: i64, nanos: u32, offset: i16) -> String {
+    let local = secs + offset as i64 * 60;
+    let (days, time) = (local.div_euclid(86400), local.rem_euclid(86400));
+
+    let mut s = format!(
+        "{}T{:02}:{:02}:{:02}",
+        format_date(days),
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    );
+    if nanos != 0 {
+        s.push_str(format!(".{nanos:09}").trim_end_matches('0'));
+    }
+    match offset {
+        0 => s.push('Z'),
+        _ => s.push_str(&format!(
+            "{}{:02}:{:02}",
+            if offset < 0 { '-' } else { '+' },
+            offset.abs() / 60,
+            offset.abs() % 60
+        )),
+    }
+    s
+}
+
+/// Write seconds as units of time, as in `1d12h`, weeks are written as days.
+pub(crate) fn format_duration(secs: i64) -> String {
+    if secs == 0 {
+        return "0s".to_string();
+    }
+
+    let mut s = if secs < 0 {
+        "-".to_string()
+    } else {
+        String::new()
+    };
+    let mut rest = secs.unsigned_abs();
+    for (unit, size) in UNITS.iter().skip(1) {
+        let n = rest / *size as u64;
+        if n != 0 {
+            s.push_str(&format!("{n}{unit}"));
+            rest %= *size as u64;
+        }
+    }
+    s
+}
@@ -0,0 +1,185 @@
+use std::net::IpAddr;
+
+/// Returns the network of an address, with bits after the prefix cleared.
+pub(crate) fn network(addr: IpAddr, len: u8) -> IpAddr {
+    let (bits, width) = bits(addr);
+    let masked = match len {
+        0 => 0,
+        len => bits & (u128::MAX << (128 - len.min(width) as u32)),
+    };
+
+    match addr {
+        IpAddr::V4(_) => IpAddr::V4(((masked >> 96) as u32).into()),
+        IpAddr::V6(_) => IpAddr::V6(masked.into()),
+    }
+}
+
+/// Returns the number of bits in an address of the same version.
+pub(crate) fn width(addr: IpAddr) -> u8 {
+    bits(addr).1
+}
+
+/// Returns the bits of an address from the most significant, and how many there are.
+fn bits(addr: IpAddr) -> (u128, u8) {
+    match addr {
+        IpAddr::V4(addr) => ((u32::from(addr) as u128) << 96, 32),
+        IpAddr::V6(addr) => (u128::from(addr), 128),
+    }
+}
+
+/// Binary trie of networks, one level per bit of the prefix.
+/// - Networks containing an address are found walking down its bits.
+#[derive(Debug)]
+pub(crate) struct PrefixTrie<T> {
+    v4: Box<Node<T>>,
+    v6: Box<Node<T>>,
+    len: usize,
+}
+
+#[derive(Debug)]
+struct Node<T> {
+    value: Option<T>,
+    children: [Option<Box<Node<T>>>; 2],
+}
+
+impl<T> Default for Node<T> {
+    fn default() -> Self {
+        Self {
+            value: None,
+            children: [None, None],
+        }
+    }
+}
+
+impl<T> Node<T> {
+    fn is_empty(&self) -> bool {
+        self.value.is_none() && self.children.iter().all(Option::is_none)
+    }
+
+    fn values<'a>(&'a self, out: &mut Vec<&'a T>) {
+        out.extend(self.value.as_ref());
+        for child in self.children.iter().flatten() {
+            child.values(out);
+        }
+    }
+
+    fn remove(&mut self, bits: u128, depth: u8, len: u8) -> Option<T> {
+        if depth == len {
+            return self.value.take();
+        }
+
+        let bit = bit(bits, depth);
+        let child = self.children[bit].as_mut()?;
+        let removed = child.remove(bits, depth + 1, len);
+        if child.is_empty() {
+            self.children[bit] = None;
+        }
+        removed
+    }
+}
+
+fn bit(bits: u128, depth: u8) -> usize {
+    (bits >> (127 - depth as u32)) as usize & 1
+}
+
+impl<T> PrefixTrie<T> {
+    /// Constructs an empty trie.
+    pub fn new() -> Self {
+        Self {
+            v4: Box::default(),
+            v6: Box::default(),
+            len: 0,
+        }
+    }
+
+    fn root(&self, addr: IpAddr) -> &Node<T> {
+        match addr {
+            IpAddr::V4(_) => &self.v4,
+            IpAddr::V6(_) => &self.v6,
+        }
+    }
+
+    fn root_mut(&mut self, addr: IpAddr) -> &mut Node<T> {
+        match addr {
+            IpAddr::V4(_) => &mut self.v4,
+            IpAddr::V6(_) => &mut self.v6,
+        }
+    }
+
+    /// Returns number of networks.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns value of the network.
+    pub fn get(&self, addr: IpAddr, len: u8) -> Option<&T> {
+        let bits = bits(addr).0;
+        let mut node = self.root(addr);
+        for depth in 0..len {
+            node = node.children[bit(bits, depth)].as_ref()?;
+        }
+        node.value.as_ref()
+    }
+
+    /// Returns value of the network.
+    pub fn get_mut(&mut self, addr: IpAddr, len: u8) -> Option<&mut T> {
+        let bits = bits(addr).0;
+        let mut node = self.root_mut(addr);
+        for depth in 0..len {
+            node = node.children[bit(bits, depth)].as_mut()?;
+        }
+        node.value.as_mut()
+    }
+
+    /// Sets value of the network, returns the replaced value.
+    pub fn insert(&mut self, addr: IpAddr, len: u8, value: T) -> Option<T> {
+        let bits = bits(addr).0;
+        let mut node = self.root_mut(addr);
+        for depth in 0..len {
+            node = node.children[bit(bits, depth)].get_or_insert_with(Box::default);
+        }
+
+        let replaced = node.value.replace(value);
+        if replaced.is_none() {
+            self.len += 1;
+        }
+        replaced
+    }
+
+    /// Removes the network, returns its value.
+    pub fn remove(&mut self, addr: IpAddr, len: u8) -> Option<T> {
+        let removed = self.root_mut(addr).remove(bits(addr).0, 0, len);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Returns values of networks containing the network, widest first.
+    pub fn covering(&self, addr: IpAddr, len: u8) -> Vec<&T> {
+        let bits = bits(addr).0;
+        let mut node = self.root(addr);
+        let mut out = Vec::new();
+
+        for depth in 0..=len {
+            out.extend(node.value.as_ref());
+            if depth == len {
+                break;
+            }
+            match node.children[bit(bits, depth)].as_ref() {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+
+        out
+    }
+
+    /// Returns values of all networks, in prefix order.
+    pub fn values(&self) -> Vec<&T> {
+        let mut out = Vec::with_capacity(self.len);
+        self.v4.values(&mut out);
+        self.v6.values(&mut out);
+        out
+    }
+}